@@ -3,8 +3,48 @@
 // Mark this module as an ink! smart contract
 #[ink::contract]
 mod vesting {
-    use ink::prelude::vec::Vec;
-    use ink::storage::Mapping;
+    use ink::prelude::{ collections::BTreeSet, string::String, vec, vec::Vec };
+    use ink::storage::{ Lazy, Mapping };
+
+    /// Version of the storage layout, bumped whenever a migration is required
+    const STORAGE_VERSION: u16 = 1;
+
+    /// Maximum length in bytes of a schedule label
+    const MAX_LABEL_LEN: usize = 64;
+
+    /// Time (in milliseconds) between an emergency withdrawal request and its execution
+    const EMERGENCY_WITHDRAW_DELAY: Timestamp = 7 * 24 * 60 * 60 * 1000;
+
+    /// Features reported as supported by `supports`
+    const SUPPORTED_FEATURES: [Feature; 18] = [
+        Feature::CliffVesting,
+        Feature::LinearVesting,
+        Feature::CliffLinearVesting,
+        Feature::TrancheVesting,
+        Feature::Psp22Mode,
+        Feature::Revocation,
+        Feature::PartialRevocation,
+        Feature::PullPayments,
+        Feature::WithdrawDelegates,
+        Feature::RelayedWithdrawals,
+        Feature::Staking,
+        Feature::VestCallback,
+        Feature::PartialWithdrawals,
+        Feature::ScheduleSplitting,
+        Feature::ScheduleMerging,
+        Feature::BlockVesting,
+        Feature::TgeVesting,
+        Feature::BeneficiaryAcceptance,
+    ];
+
+    /// Highest protocol fee the admin can set, in basis points (10%)
+    const MAX_FEE_BPS: u16 = 1000;
+
+    /// Highest tip a relayer can take from a relayed withdrawal, in basis points (1%)
+    const MAX_RELAYER_TIP_BPS: u16 = 100;
+
+    /// Number of most recent claims kept in the history of a beneficiary
+    const MAX_CLAIM_HISTORY: usize = 50;
 
     //----------------------------------
     // Error Handling
@@ -17,22 +57,388 @@ mod vesting {
         NoFundsAvailable = 1, // When no funds are available for withdrawal
         TransferFailed = 2, // When token transfer fails
         IdOverflow = 3, // When schedule ID overflows
+        NotOwner = 4, // When the caller is not the owner of the schedule
+        AlreadyVested = 5, // When the schedule has already started vesting
+        ScheduleNotFound = 6, // When no schedule exists for the given ID
+        NotBeneficiary = 7, // When the caller is not the beneficiary of the schedule
+        NotYetUnlocked = 8, // When the schedule has no funds available yet
+        TokenCallFailed = 9, // When a call to the PSP22 token contract fails
+        InvalidSchedule = 10, // When the schedule parameters are inconsistent
+        NotAdmin = 11, // When the caller is not the contract admin
+        Paused = 12, // When the contract is paused
+        Reentrancy = 13, // When a withdrawal is re-entered while in progress
+        UnlockInPast = 14, // When the unlock time is not in the future
+        UnlockTooFar = 15, // When the unlock time is beyond the allowed horizon
+        NotPendingAdmin = 16, // When the caller is not the proposed admin
+        AmountMismatch = 17, // When the batch amounts do not add up to the transferred value
+        NoSchedules = 18, // When the caller has no vesting schedules
+        NotAuthorized = 19, // When the caller is not the beneficiary's withdraw delegate
+        DepositsFrozen = 20, // When new deposits are frozen
+        LabelTooLong = 21, // When a schedule label exceeds the maximum length
+        TimelockNotElapsed = 22, // When an emergency withdrawal was not requested long enough ago
+        AmountOverflow = 23, // When adding up amounts overflows
+        FeeTooHigh = 24, // When the protocol fee exceeds the maximum
+        NotRevocable = 25, // When revoking a schedule created as irrevocable
+        CooldownActive = 26, // When withdrawing again before the cooldown has passed
+        InvalidBeneficiary = 27, // When the beneficiary is the zero address or the contract itself
+        TooManySchedules = 28, // When the beneficiary already has the maximum number of schedules
+        InsufficientValue = 29, // When the transferred value is below the requested amount
+        CannotShorten = 30, // When the new unlock time is not later than the current one
+        InvalidUnlockTime = 31, // When the unlock time is zero, most likely left unset
+        StakeFailed = 32, // When forwarding withdrawn funds to a staking contract fails
+        BeneficiaryLocked = 33, // When reassigning a schedule its beneficiary has locked
+        NotCoSigner = 34, // When the caller is not the co-signer approving large revocations
+        RevokeNotApproved = 35, // When revoking a large schedule the co-signer has not approved
+        TipTooHigh = 36, // When the relayer tip exceeds the maximum
+        ExceedsAvailable = 37, // When withdrawing more than the funds available
+        WrongMode = 38, // When depositing native funds into a token contract, or the reverse
+        OutflowCapReached = 39, // When a withdrawal would exceed the outflow cap of the current window
+        InvalidSplit = 40, // When splitting off the whole amount of a schedule or more
+        CannotMerge = 41, // When merging schedules whose terms differ
+        NotWhitelisted = 42, // When creating a schedule without being on the owner whitelist
+        AmountTooLarge = 43, // When a schedule would hold more than the maximum schedule amount
+        NotAccepted = 44, // When withdrawing from a schedule its beneficiary has not accepted yet
+        RelayNotAllowed = 45, // When relaying a withdrawal for a beneficiary that did not allow relaying
+    }
+
+    impl Error {
+        /// Returns a human-readable description of the error.
+        fn message(&self) -> &'static str {
+            match self {
+                Error::ZeroAmount => "The amount must not be zero",
+                Error::NoFundsAvailable => "No funds are available for withdrawal",
+                Error::TransferFailed => "The transfer failed",
+                Error::IdOverflow => "The schedule ID counter overflowed",
+                Error::NotOwner => "The caller is not the owner of the schedule",
+                Error::AlreadyVested => "The schedule has already started vesting",
+                Error::ScheduleNotFound => "No schedule exists for this ID",
+                Error::NotBeneficiary => "The caller is not the beneficiary of the schedule",
+                Error::NotYetUnlocked => "The schedule has no funds available yet",
+                Error::TokenCallFailed => "The call to the token contract failed",
+                Error::InvalidSchedule => "The schedule parameters are inconsistent",
+                Error::NotAdmin => "The caller is not the admin",
+                Error::Paused => "The contract is paused",
+                Error::Reentrancy => "A withdrawal is already in progress",
+                Error::UnlockInPast => "The unlock time must be in the future",
+                Error::UnlockTooFar => "The unlock time is beyond the allowed horizon",
+                Error::NotPendingAdmin => "The caller is not the proposed admin",
+                Error::AmountMismatch => "The amounts do not add up to the transferred value",
+                Error::NoSchedules => "The account has no vesting schedules",
+                Error::NotAuthorized => "The caller is not the withdraw delegate of the beneficiary",
+                Error::DepositsFrozen => "New deposits are frozen",
+                Error::LabelTooLong => "The label is too long",
+                Error::TimelockNotElapsed => "The emergency withdrawal timelock has not elapsed",
+                Error::AmountOverflow => "The amounts overflow when added up",
+                Error::FeeTooHigh => "The fee exceeds the maximum",
+                Error::NotRevocable => "The schedule is irrevocable",
+                Error::CooldownActive => "The withdrawal cooldown has not elapsed",
+                Error::InvalidBeneficiary => "The beneficiary cannot receive funds",
+                Error::TooManySchedules => "The beneficiary has the maximum number of schedules",
+                Error::InsufficientValue => "The transferred value is below the amount",
+                Error::CannotShorten => "The unlock time can only be postponed",
+                Error::InvalidUnlockTime => "The unlock time must be set",
+                Error::StakeFailed => "Staking the withdrawn funds failed",
+                Error::BeneficiaryLocked => "The beneficiary of the schedule is locked",
+                Error::NotCoSigner => "The caller is not the co-signer",
+                Error::RevokeNotApproved => "The co-signer has not approved the revocation",
+                Error::TipTooHigh => "The relayer tip exceeds the maximum",
+                Error::ExceedsAvailable => "The amount exceeds the funds available",
+                Error::WrongMode => "The contract does not vest this kind of funds",
+                Error::OutflowCapReached => "The withdrawal exceeds the outflow cap of the current window",
+                Error::InvalidSplit => "The split amount must be less than the schedule amount",
+                Error::CannotMerge => "The schedules do not have identical terms",
+                Error::NotWhitelisted => "The caller is not allowed to create schedules",
+                Error::AmountTooLarge => "The schedule amount exceeds the maximum",
+                Error::NotAccepted => "The beneficiary has not accepted the schedule yet",
+                Error::RelayNotAllowed => "The beneficiary does not allow relayed withdrawals",
+            }
+        }
     }
 
     /// Type alias for Result that uses our custom Error
     pub type Result<T> = core::result::Result<T, Error>;
 
+    /// Time and amount paid of the claims of a beneficiary, oldest first
+    pub type ClaimHistory = Vec<(Timestamp, Balance)>;
+
+    //----------------------------------
+    // Events
+    //----------------------------------
+    /// Emitted when funds are deposited into a new vesting schedule
+    #[ink(event)]
+    pub struct Deposited {
+        #[ink(topic)]
+        beneficiary: AccountId, // Who can claim the funds
+        #[ink(topic)]
+        owner: AccountId, // Who created the vesting schedule
+        id: u64, // ID of the new schedule
+        amount: Balance, // Amount vested
+        unlock_time: Timestamp, // When the full amount becomes available, 0 for block schedules
+    }
+
+    /// Emitted when a beneficiary withdraws vested funds
+    #[ink(event)]
+    pub struct Withdrawn {
+        #[ink(topic)]
+        beneficiary: AccountId, // Who received the funds
+        total_amount: Balance, // Amount transferred
+        schedule_ids: Vec<u64>, // Schedules that released funds in this withdrawal
+    }
+
+    /// Emitted when a schedule is fully withdrawn and removed
+    #[ink(event)]
+    pub struct ScheduleClosed {
+        #[ink(topic)]
+        id: u64, // ID of the closed schedule
+        beneficiary: AccountId, // Who received the funds
+    }
+
+    /// Emitted when released funds could not be transferred and were credited instead.
+    /// This is how failed withdrawal transfers show up on-chain: they do not revert.
+    #[ink(event)]
+    pub struct Credited {
+        #[ink(topic)]
+        beneficiary: AccountId, // Who can claim the credit
+        amount: Balance, // Amount credited
+    }
+
+    /// Emitted when an owner adds funds to an existing schedule
+    #[ink(event)]
+    pub struct ToppedUp {
+        #[ink(topic)]
+        id: u64, // ID of the schedule
+        amount: Balance, // Amount added
+        new_amount: Balance, // Total amount of the schedule after the top-up
+    }
+
+    /// Emitted when an owner postpones the unlock time of a schedule
+    #[ink(event)]
+    pub struct UnlockExtended {
+        #[ink(topic)]
+        id: u64, // ID of the schedule
+        new_unlock_time: Timestamp, // Unlock time after the extension
+    }
+
+    /// Emitted when an owner redirects a schedule to a new beneficiary
+    #[ink(event)]
+    pub struct BeneficiaryReassigned {
+        #[ink(topic)]
+        id: u64, // ID of the reassigned schedule
+        #[ink(topic)]
+        old_beneficiary: AccountId, // Who could claim the funds before
+        #[ink(topic)]
+        new_beneficiary: AccountId, // Who can claim the funds now
+    }
+
+    /// Emitted when a beneficiary accepts a schedule created pending their acceptance
+    #[ink(event)]
+    pub struct ScheduleAccepted {
+        #[ink(topic)]
+        id: u64, // ID of the accepted schedule
+        #[ink(topic)]
+        beneficiary: AccountId, // Who accepted the schedule
+    }
+
+    /// Emitted when the admin moves a schedule to a new owner
+    #[ink(event)]
+    pub struct OwnerReassigned {
+        #[ink(topic)]
+        id: u64, // ID of the reassigned schedule
+        #[ink(topic)]
+        old_owner: AccountId, // Who managed the schedule before
+        #[ink(topic)]
+        new_owner: AccountId, // Who manages the schedule now
+    }
+
+    /// Emitted when an owner splits part of a schedule off into a new one
+    #[ink(event)]
+    pub struct Split {
+        #[ink(topic)]
+        id: u64, // ID of the split schedule
+        new_id: u64, // ID of the new schedule
+        amount: Balance, // Amount moved to the new schedule
+    }
+
+    /// Emitted when an owner merges schedules with identical terms into a new one
+    #[ink(event)]
+    pub struct Merged {
+        #[ink(topic)]
+        id: u64, // ID of the new schedule
+        merged_ids: Vec<u64>, // IDs of the removed schedules
+        amount: Balance, // Amount of the new schedule
+    }
+
+    /// Emitted when the admin changes a configuration parameter
+    #[ink(event)]
+    pub struct ConfigChanged {
+        #[ink(topic)]
+        param: u8, // Discriminant of the `ConfigParam` that changed
+        old_value: u128, // Value before the change
+        new_value: u128, // Value after the change
+    }
+
+    /// Emitted when the admin changes an account-valued configuration parameter
+    #[ink(event)]
+    pub struct ConfigAccountChanged {
+        #[ink(topic)]
+        param: u8, // Discriminant of the `ConfigParam` that changed
+        old_account: Option<AccountId>, // Account before the change, `None` if unset
+        new_account: Option<AccountId>, // Account after the change, `None` if unset
+    }
+
+    /// Emitted when the admin role is handed over to a new account
+    #[ink(event)]
+    pub struct AdminTransferred {
+        #[ink(topic)]
+        old_admin: AccountId, // Previous admin
+        #[ink(topic)]
+        new_admin: AccountId, // New admin
+    }
+
+    /// Emitted when an owner revokes a schedule and reclaims its funds
+    #[ink(event)]
+    pub struct Revoked {
+        #[ink(topic)]
+        id: u64, // ID of the revoked schedule
+        #[ink(topic)]
+        owner: AccountId, // Who received the refund
+        amount: Balance, // Amount refunded
+    }
+
+    /// Emitted when a beneficiary renounces a schedule and its funds return to the owner
+    #[ink(event)]
+    pub struct Renounced {
+        #[ink(topic)]
+        id: u64, // ID of the renounced schedule
+        #[ink(topic)]
+        beneficiary: AccountId, // Who renounced the funds
+        amount: Balance, // Amount returned to the owner
+    }
+
+    /// Emitted when the notification contract could not be told about a withdrawal
+    #[ink(event)]
+    pub struct NotifyFailed {
+        #[ink(topic)]
+        notify_contract: AccountId, // Contract that was notified
+        #[ink(topic)]
+        beneficiary: AccountId, // Who withdrew the funds
+        amount: Balance, // Amount withdrawn
+    }
+
+    /// Emitted when the admin requests an emergency withdrawal of a schedule
+    #[ink(event)]
+    pub struct EmergencyWithdrawRequested {
+        #[ink(topic)]
+        id: u64, // ID of the schedule
+        execute_after: Timestamp, // Earliest time the withdrawal can be executed
+    }
+
+    /// Emitted when the admin takes the funds of a schedule in an emergency
+    #[ink(event)]
+    pub struct EmergencyWithdrawn {
+        #[ink(topic)]
+        id: u64, // ID of the removed schedule
+        #[ink(topic)]
+        admin: AccountId, // Who received the funds
+        amount: Balance, // Amount withdrawn
+    }
+
     //----------------------------------
     // Contract Storage
     //----------------------------------
     #[ink(storage)]
     pub struct Vesting {
-        // Auto-incrementing ID for vesting schedules
+        // Version of the storage layout, used to detect old layouts after an upgrade
+        version: u16,
+        // Auto-incrementing ID for vesting schedules, never reused
         id: u64,
-        // Mapping from schedule ID to vesting details
-        schedules: Mapping<u64, VestingSchedule>,
+        // Mapping from schedule ID to vesting details, accessed through
+        // `load_schedule` and `store_schedule`
+        schedules: Mapping<u64, StoredSchedule>,
         // Mapping from beneficiary to their schedule IDs
         beneficiary_to_ids: Mapping<AccountId, Vec<u64>>,
+        // Mapping from owner to the IDs of the schedules they created
+        owner_to_ids: Mapping<AccountId, Vec<u64>>,
+        // Whether the vested funds are the native balance or a PSP22 token
+        mode: VestingMode,
+        // Mapping from account to released funds whose transfer failed
+        credited_balances: Mapping<AccountId, Balance>,
+        // Sum of the funds credited to all accounts
+        total_credited: Balance,
+        // Account allowed to perform privileged actions
+        admin: AccountId,
+        // Account proposed as the next admin, until it accepts the role
+        pending_admin: Option<AccountId>,
+        // Whether deposits and withdrawals are frozen
+        paused: bool,
+        // Whether new deposits are frozen, withdrawals are still allowed
+        deposits_frozen: bool,
+        // Whether a withdrawal is in progress. Kept in `Lazy` so it is written to
+        // storage immediately and visible to re-entrant calls.
+        reentrancy_lock: Lazy<bool>,
+        // Maximum time between a deposit and its unlock, `None` for no limit
+        max_unlock_horizon: Option<Timestamp>,
+        // Sum of the funds held by all schedules
+        total_locked: Balance,
+        // Sum of the funds released to beneficiaries
+        total_withdrawn: Balance,
+        // Number of schedules currently stored
+        schedule_count: u64,
+        // Mapping from position to the ID of a stored schedule, positions are
+        // `0..schedule_count`. A `Vec` would be rewritten in full on every deposit and
+        // withdrawal, while these two mappings only touch the moved entries.
+        active_ids: Mapping<u64, u64>,
+        // Mapping from schedule ID to its position in `active_ids`
+        active_positions: Mapping<u64, u64>,
+        // Mapping from beneficiary to the funds held by their schedules
+        locked_by_beneficiary: Mapping<AccountId, Balance>,
+        // Mapping from beneficiary to the account allowed to withdraw on their behalf
+        withdraw_delegates: Mapping<AccountId, AccountId>,
+        // Mapping from beneficiary to the highest relayer tip they accept, in basis points
+        relayer_tip_caps: Mapping<AccountId, u16>,
+        // Mapping from schedule ID to the time its emergency withdrawal was requested
+        emergency_requests: Mapping<u64, Timestamp>,
+        // Protocol fee deducted from withdrawals, in basis points
+        fee_bps: u16,
+        // Account receiving the protocol fee
+        fee_recipient: AccountId,
+        // Minimum time (in milliseconds) between two withdrawals of a beneficiary, 0 for none
+        cooldown_ms: Timestamp,
+        // Mapping from beneficiary to the time of their last withdrawal
+        last_withdraw: Mapping<AccountId, Timestamp>,
+        // Maximum number of schedules per beneficiary, 0 for no limit
+        max_schedules_per_beneficiary: u32,
+        // Largest amount a single schedule can hold, 0 for no limit
+        max_schedule_amount: Balance,
+        // Contract notified of every withdrawal, `None` for no notification
+        notify_contract: Option<AccountId>,
+        // Mapping from beneficiary to the time and amount of their most recent claims
+        claim_history: Mapping<AccountId, ClaimHistory>,
+        // Mapping from beneficiary to the sum of all the funds paid to them
+        total_received: Mapping<AccountId, Balance>,
+        // Mapping from beneficiary to the summed delay between the unlock time and the
+        // final claim of their fully released schedules, and the number of those schedules
+        claim_delays: Mapping<AccountId, (Timestamp, u64)>,
+        // Revocations refunding more than this amount need the co-signer's approval, 0 for none
+        large_amount_threshold: Balance,
+        // Account approving large revocations
+        co_signer: Option<AccountId>,
+        // Mapping from schedule ID to whether the co-signer approved its revocation
+        revoke_approvals: Mapping<u64, bool>,
+        // Mapping from account to whether it receives its payouts through `on_vest_received`
+        vest_callbacks: Mapping<AccountId, bool>,
+        // Most funds withdrawn from all schedules within an outflow window, 0 for no cap
+        max_outflow_per_window: Balance,
+        // Length of an outflow window
+        outflow_window_ms: Timestamp,
+        // Start of the current outflow window
+        outflow_window_start: Timestamp,
+        // Funds withdrawn from all schedules since the start of the current outflow window
+        outflow_in_window: Balance,
+        // Accounts allowed to create schedules while the whitelist is enabled
+        owner_whitelist: Mapping<AccountId, bool>,
+        // Whether only whitelisted owners can create schedules
+        whitelist_enabled: bool,
     }
 
     //----------------------------------
@@ -42,9 +448,49 @@ mod vesting {
     impl Default for Vesting {
         fn default() -> Self {
             Self {
+                version: STORAGE_VERSION,
                 id: 0,
                 schedules: Mapping::new(),
                 beneficiary_to_ids: Mapping::new(),
+                owner_to_ids: Mapping::new(),
+                mode: VestingMode::Native,
+                credited_balances: Mapping::new(),
+                total_credited: 0,
+                admin: ink::env::caller::<ink::env::DefaultEnvironment>(),
+                pending_admin: None,
+                paused: false,
+                deposits_frozen: false,
+                reentrancy_lock: Lazy::new(),
+                max_unlock_horizon: None,
+                total_locked: 0,
+                total_withdrawn: 0,
+                schedule_count: 0,
+                active_ids: Mapping::default(),
+                active_positions: Mapping::default(),
+                locked_by_beneficiary: Mapping::new(),
+                withdraw_delegates: Mapping::new(),
+                relayer_tip_caps: Mapping::new(),
+                emergency_requests: Mapping::new(),
+                fee_bps: 0,
+                fee_recipient: ink::env::caller::<ink::env::DefaultEnvironment>(),
+                cooldown_ms: 0,
+                last_withdraw: Mapping::new(),
+                max_schedules_per_beneficiary: 0,
+                max_schedule_amount: 0,
+                notify_contract: None,
+                claim_history: Mapping::default(),
+                total_received: Mapping::default(),
+                claim_delays: Mapping::default(),
+                large_amount_threshold: 0,
+                co_signer: None,
+                revoke_approvals: Mapping::default(),
+                vest_callbacks: Mapping::default(),
+                max_outflow_per_window: 0,
+                outflow_window_ms: 0,
+                outflow_window_start: 0,
+                outflow_in_window: 0,
+                owner_whitelist: Mapping::default(),
+                whitelist_enabled: false,
             }
         }
     }
@@ -52,6 +498,61 @@ mod vesting {
     //----------------------------------
     // Vesting Schedule Structure
     //----------------------------------
+    /// Describes how the funds of a schedule are released over time
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            scale_info::TypeInfo, // Required for metadata generation
+            ink::storage::traits::StorageLayout // Required for storage mapping
+        )
+    )]
+    enum VestingKind {
+        // The full amount becomes available at `unlock_time`, inclusive
+        Cliff {
+            unlock_time: Timestamp,
+        },
+        // The amount vests proportionally from `start_time` over `duration`
+        Linear {
+            start_time: Timestamp,
+            duration: Timestamp,
+        },
+        // Each `(time, amount)` tranche becomes available at its time. Released
+        // tranches are dropped, so only the pending ones are kept in storage, except
+        // the last tranche which is always kept as the unlock time of the schedule.
+        Tranches {
+            tranches: Vec<(Timestamp, Balance)>,
+        },
+        // The full amount becomes available at block `unlock_block`, inclusive
+        Block {
+            unlock_block: BlockNumber,
+        },
+        // `tge_amount` is available right away, the rest vests like `Linear`
+        LinearWithTge {
+            tge_amount: Balance,
+            start_time: Timestamp,
+            duration: Timestamp,
+        },
+    }
+
+    impl VestingKind {
+        /// Returns the timestamp at which the full amount is vested, or `None` if the
+        /// schedule unlocks at a block number.
+        fn unlock_time(&self) -> Option<Timestamp> {
+            match *self {
+                VestingKind::Cliff { unlock_time } => Some(unlock_time),
+                VestingKind::Linear { start_time, duration } => Some(start_time.saturating_add(duration)),
+                VestingKind::Tranches { ref tranches } => {
+                    Some(tranches.last().map(|&(time, _)| time).unwrap_or_default())
+                }
+                VestingKind::Block { .. } => None,
+                VestingKind::LinearWithTge { start_time, duration, .. } => {
+                    Some(start_time.saturating_add(duration))
+                }
+            }
+        }
+    }
+
     /// Represents a single vesting schedule
     #[derive(Debug, Clone, scale::Encode, scale::Decode)]
     #[cfg_attr(
@@ -65,264 +566,8642 @@ mod vesting {
         owner: AccountId, // Who created the vesting schedule
         beneficiary: AccountId, // Who can claim the funds
         amount: Balance, // Amount to be vested
-        unlock_time: Timestamp, // When funds become available for withdrawal
+        released: Balance, // Amount already withdrawn by the beneficiary
+        kind: VestingKind, // How the amount is released over time
+        label: Option<Vec<u8>>, // Optional note for bookkeeping, e.g. "Q3 bonus"
+        revocable: bool, // Whether the owner can revoke the schedule
+        beneficiary_locked: bool, // Whether the beneficiary refused any reassignment
+        pending_acceptance: bool, // Whether the beneficiary still has to accept the schedule
+    }
+
+    impl VestingSchedule {
+        /// Returns the total amount vested at `now`, including what was already released.
+        /// Block schedules are checked against the block number `current_block` instead.
+        fn vested_amount(&self, now: Timestamp, current_block: BlockNumber) -> Balance {
+            match self.kind {
+                VestingKind::Cliff { unlock_time } => {
+                    if unlock_time <= now { self.amount } else { 0 }
+                }
+                VestingKind::Linear { start_time, duration } => {
+                    linear_vested(self.amount, start_time, duration, now)
+                }
+                VestingKind::Tranches { ref tranches } => {
+                    // Everything but the pending tranches is vested
+                    let pending: Balance = tranches
+                        .iter()
+                        .filter(|&&(time, _)| time > now)
+                        .map(|&(_, amount)| amount)
+                        .sum();
+                    self.amount.saturating_sub(pending)
+                }
+                VestingKind::Block { unlock_block } => {
+                    if unlock_block <= current_block { self.amount } else { 0 }
+                }
+                VestingKind::LinearWithTge { tge_amount, start_time, duration } => {
+                    // Never vest more than the amount, should it drop below the TGE amount
+                    let linear_amount = self.amount.saturating_sub(tge_amount);
+                    tge_amount.min(self.amount) + linear_vested(linear_amount, start_time, duration, now)
+                }
+            }
+        }
+
+        /// Returns the amount vested at `now` and `current_block` that has not been released
+        /// yet. Nothing is claimable before the beneficiary accepted the schedule.
+        fn claimable(&self, now: Timestamp, current_block: BlockNumber) -> Balance {
+            if self.pending_acceptance {
+                return 0;
+            }
+            self.vested_amount(now, current_block) - self.released
+        }
+
+        /// Returns whether part of the schedule already belongs to the beneficiary at
+        /// `now` and `current_block`. Nothing does before the beneficiary accepted the schedule.
+        fn has_vested(&self, now: Timestamp, current_block: BlockNumber) -> bool {
+            !self.pending_acceptance && self.vested_amount(now, current_block) > 0
+        }
+
+        /// Returns the public view of the schedule at `now` and `current_block`.
+        fn info(&self, now: Timestamp, current_block: BlockNumber) -> VestingScheduleInfo {
+            VestingScheduleInfo {
+                owner: self.owner,
+                beneficiary: self.beneficiary,
+                amount: self.amount,
+                released: self.released,
+                unlock_time: self.kind.unlock_time().unwrap_or_default(),
+                unlock_block: match self.kind {
+                    VestingKind::Block { unlock_block } => Some(unlock_block),
+                    _ => None,
+                },
+                claimable: self.claimable(now, current_block),
+                label: self.label.clone(),
+                revocable: self.revocable,
+                pending_acceptance: self.pending_acceptance,
+            }
+        }
+    }
+
+    /// Storage encoding of a `VestingSchedule`. Most schedules vest the owner's own
+    /// funds to themselves, so the beneficiary is only stored when it differs.
+    #[derive(Debug, Clone, scale::Encode, scale::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            scale_info::TypeInfo, // Required for metadata generation
+            ink::storage::traits::StorageLayout // Required for storage mapping
+        )
+    )]
+    struct StoredSchedule {
+        owner: AccountId, // Who created the vesting schedule
+        beneficiary: Option<AccountId>, // Who can claim the funds, `None` if it is the owner
+        amount: Balance, // Amount to be vested
+        released: Balance, // Amount already withdrawn by the beneficiary
+        kind: VestingKind, // How the amount is released over time
+        label: Option<Vec<u8>>, // Optional note for bookkeeping, e.g. "Q3 bonus"
+        revocable: bool, // Whether the owner can revoke the schedule
+        beneficiary_locked: bool, // Whether the beneficiary refused any reassignment
+        pending_acceptance: bool, // Whether the beneficiary still has to accept the schedule
+    }
+
+    impl From<&VestingSchedule> for StoredSchedule {
+        fn from(schedule: &VestingSchedule) -> Self {
+            StoredSchedule {
+                owner: schedule.owner,
+                beneficiary: (schedule.beneficiary != schedule.owner).then_some(schedule.beneficiary),
+                amount: schedule.amount,
+                released: schedule.released,
+                kind: schedule.kind.clone(),
+                label: schedule.label.clone(),
+                revocable: schedule.revocable,
+                beneficiary_locked: schedule.beneficiary_locked,
+                pending_acceptance: schedule.pending_acceptance,
+            }
+        }
+    }
+
+    impl From<StoredSchedule> for VestingSchedule {
+        fn from(stored: StoredSchedule) -> Self {
+            VestingSchedule {
+                owner: stored.owner,
+                beneficiary: stored.beneficiary.unwrap_or(stored.owner),
+                amount: stored.amount,
+                released: stored.released,
+                kind: stored.kind,
+                label: stored.label,
+                revocable: stored.revocable,
+                beneficiary_locked: stored.beneficiary_locked,
+                pending_acceptance: stored.pending_acceptance,
+            }
+        }
+    }
+
+    /// Public view of a vesting schedule returned by the getters
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct VestingScheduleInfo {
+        pub owner: AccountId, // Who created the vesting schedule
+        pub beneficiary: AccountId, // Who can claim the funds
+        pub amount: Balance, // Amount to be vested
+        pub released: Balance, // Amount already withdrawn by the beneficiary
+        pub unlock_time: Timestamp, // When the full amount becomes available, 0 for block schedules
+        pub unlock_block: Option<BlockNumber>, // Block at which the full amount becomes available, if any
+        pub claimable: Balance, // Amount that can be withdrawn right now
+        pub label: Option<Vec<u8>>, // Optional note attached by the owner
+        pub revocable: bool, // Whether the owner can revoke the schedule
+        pub pending_acceptance: bool, // Whether the beneficiary still has to accept the schedule
+    }
+
+    /// Contract-wide statistics returned by `stats`
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct ContractStats {
+        pub total_schedules: u64, // Number of schedules currently stored
+        pub next_id: u64, // ID the next schedule will get
+        pub total_locked: Balance, // Sum of the funds held by all schedules
+        pub total_withdrawn: Balance, // Sum of the funds released to beneficiaries
+    }
+
+    /// Overview of a beneficiary's funds returned by `dashboard`
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct BeneficiaryDashboard {
+        pub claimable: Balance, // Funds that can be withdrawn right now
+        pub locked: Balance, // Funds that have not vested yet
+        pub next_unlock: Option<Timestamp>, // Earliest time a schedule becomes fully vested
+        pub schedule_count: u32, // Number of schedules of the beneficiary
+        pub total_received: Balance, // Sum of the funds ever paid to the beneficiary
+    }
+
+    //----------------------------------
+    // PSP22 Token Integration
+    //----------------------------------
+    /// Error returned by a PSP22 token contract
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum PSP22Error {
+        Custom(String),
+        InsufficientBalance,
+        InsufficientAllowance,
+        ZeroRecipientAddress,
+        ZeroSenderAddress,
+        SafeTransferCheckFailed(String),
+    }
+
+    /// Configuration parameters reported by `ConfigChanged`, flags are reported as 0 or 1
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum ConfigParam {
+        Paused = 0, // Whether deposits and withdrawals are frozen
+        DepositsFrozen = 1, // Whether new deposits are frozen
+        FeeBps = 2, // Protocol fee in basis points
+        CooldownMs = 3, // Minimum time between two withdrawals of a beneficiary
+        MaxSchedulesPerBeneficiary = 4, // Maximum number of schedules per beneficiary
+        LargeAmountThreshold = 5, // Largest refund revocable without the co-signer's approval
+        MaxOutflowPerWindow = 6, // Most funds withdrawn in an outflow window
+        OutflowWindowMs = 7, // Length of an outflow window
+        WhitelistEnabled = 8, // Whether only whitelisted owners can create schedules
+        MaxScheduleAmount = 9, // Largest amount a single schedule can hold
+        FeeRecipient = 10, // Account receiving the protocol fee
+        NotifyContract = 11, // Contract notified after every withdrawal
+        CoSigner = 12, // Account approving revocations of large schedules
+    }
+
+    /// Capabilities reported by `supports`. New features get the next free ID and are
+    /// added to `SUPPORTED_FEATURES`, IDs are never reused.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Feature {
+        CliffVesting = 0, // `deposit_fund` and its variants
+        LinearVesting = 1, // `deposit_fund_linear`
+        CliffLinearVesting = 2, // `deposit_fund_cliff_linear`
+        TrancheVesting = 3, // `deposit_fund_tranches`
+        Psp22Mode = 4, // Instances created with `new_psp22`
+        Revocation = 5, // `revoke` and `revoke_all_for`
+        PartialRevocation = 6, // `revoke_partial`
+        PullPayments = 7, // Failed transfers credited for `claim_credit`
+        WithdrawDelegates = 8, // `withdraw_for`
+        RelayedWithdrawals = 9, // `withdraw_fund_relayed`
+        Staking = 10, // `withdraw_and_stake`
+        VestCallback = 11, // `set_vest_callback`
+        PartialWithdrawals = 12, // `withdraw_partial`
+        ScheduleSplitting = 13, // `split_schedule`
+        ScheduleMerging = 14, // `merge_schedules`
+        BlockVesting = 15, // `deposit_fund_by_block`
+        TgeVesting = 16, // `deposit_fund_tge`
+        BeneficiaryAcceptance = 17, // `deposit_fund_with_acceptance` and `accept_schedule`
+    }
+
+    /// Which funds a contract instance vests, fixed at instantiation
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(
+            scale_info::TypeInfo, // Required for metadata generation
+            ink::storage::traits::StorageLayout // Required for storage mapping
+        )
+    )]
+    pub enum VestingMode {
+        // The native balance, deposited with the transferred value
+        Native,
+        // The PSP22 token at this address, deposited with `deposit_fund_token`
+        Token(AccountId),
+    }
+
+    /// Where the funds released by a withdrawal are sent
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Payout {
+        // Transferred to the account, or credited to it if the transfer fails
+        Transfer(AccountId),
+        // Staked on the staking contract on behalf of the beneficiary
+        Stake(AccountId),
+        // Transferred to the beneficiary, minus a tip for the relayer
+        Relay {
+            relayer: AccountId,
+            tip_bps: u16,
+        },
+    }
+
+    /// A call made by the contract to its PSP22 token
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    enum TokenCall {
+        // Moves tokens held by the contract to `to`
+        Transfer {
+            to: AccountId,
+            value: Balance,
+        },
+        // Moves tokens from `from` to `to` using the allowance granted to the contract
+        TransferFrom {
+            from: AccountId,
+            to: AccountId,
+            value: Balance,
+        },
+    }
+
+    /// Returns `bps` basis points of `amount`, rounded down.
+    fn bps_of(amount: Balance, bps: u16) -> Balance {
+        // amount * bps / 10000, split so the product cannot overflow
+        let bps = Balance::from(bps);
+        (amount / 10_000) * bps + ((amount % 10_000) * bps) / 10_000
+    }
+
+    /// Adds `amount` to a running total of locked funds, failing with
+    /// `Error::AmountOverflow` if it overflows.
+    ///
+    /// The default rejects the deposit that would overflow, so the totals are always
+    /// exact. Build with the `saturating-counters` feature to cap the totals at
+    /// `Balance::MAX` instead: deposits are never rejected, but a capped total no
+    /// longer matches the schedules.
+    #[cfg(not(feature = "saturating-counters"))]
+    fn add_to_counter(total: Balance, amount: Balance) -> Result<Balance> {
+        total.checked_add(amount).ok_or(Error::AmountOverflow)
+    }
+
+    /// Adds `amount` to a running total of locked funds, capped at `Balance::MAX`.
+    /// See the checked version for the tradeoff.
+    #[cfg(feature = "saturating-counters")]
+    fn add_to_counter(total: Balance, amount: Balance) -> Result<Balance> {
+        Ok(total.saturating_add(amount))
+    }
+
+    /// Returns the part of `amount` vested at `now` when it vests proportionally from
+    /// `start_time` over `duration`.
+    fn linear_vested(amount: Balance, start_time: Timestamp, duration: Timestamp, now: Timestamp) -> Balance {
+        if now < start_time {
+            return 0;
+        }
+        let elapsed = now - start_time;
+        if elapsed >= duration {
+            return amount;
+        }
+        // amount * elapsed / duration, split as (q * d + r) * e / d
+        // so the intermediate product can never overflow u128
+        let elapsed = Balance::from(elapsed);
+        let duration = Balance::from(duration);
+        (amount / duration) * elapsed + ((amount % duration) * elapsed) / duration
     }
 
     //----------------------------------
     // Core Contract Logic
     //----------------------------------
     impl Vesting {
-        /// Constructor that initializes the contract
+        /// Constructor that initializes the contract, with the deployer as admin
         #[ink(constructor)]
         pub fn new() -> Self {
             Self::default()
         }
 
+        /// Constructor that initializes the contract with an explicit admin.
+        ///
+        /// # Arguments
+        ///
+        /// * `admin`: The account allowed to perform privileged actions.
+        #[ink(constructor)]
+        pub fn new_with_admin(admin: AccountId) -> Self {
+            Self {
+                admin,
+                ..Self::default()
+            }
+        }
+
+        /// Constructor that initializes the contract with a limit on how far in the
+        /// future schedules can unlock.
+        ///
+        /// # Arguments
+        ///
+        /// * `max_unlock_horizon`: The maximum time (in milliseconds) between a deposit
+        ///   and the moment its funds are fully vested.
+        #[ink(constructor)]
+        pub fn new_with_max_horizon(max_unlock_horizon: Timestamp) -> Self {
+            Self {
+                max_unlock_horizon: Some(max_unlock_horizon),
+                ..Self::default()
+            }
+        }
+
+        /// Constructor that initializes the contract with an initial allocation table.
+        ///
+        /// The schedules are owned by the deployer and funded by the value transferred
+        /// with the instantiation, which must equal the sum of the entry amounts.
+        ///
+        /// # Arguments
+        ///
+        /// * `entries`: The `(beneficiary, unlock_time, amount)` of each schedule to create.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::AmountOverflow` if the amounts overflow when added up.
+        /// Returns `Error::AmountMismatch` if the amounts do not add up to the transferred value.
+        /// Returns `Error::ZeroAmount` if any amount is zero.
+        /// Returns `Error::UnlockInPast` if any funds would be fully vested already.
+        /// Returns `Error::InvalidUnlockTime` if the unlock time is zero.
+        /// Returns `Error::InvalidBeneficiary` if the beneficiary is the zero address or the contract itself.
+        /// Returns `Error::TooManySchedules` if the beneficiary already has the maximum number of schedules.
+        #[ink(constructor, payable)]
+        pub fn new_with_schedules(entries: Vec<(AccountId, Timestamp, Balance)>) -> Result<Self> {
+            let mut contract = Self::default();
+            contract.create_schedules(entries)?;
+            Ok(contract)
+        }
+
+        /// Constructor that initializes the contract to vest a PSP22 token
+        /// instead of the native balance.
+        ///
+        /// # Arguments
+        ///
+        /// * `token`: The address of the PSP22 token contract.
+        #[ink(constructor)]
+        pub fn new_psp22(token: AccountId) -> Self {
+            Self {
+                mode: VestingMode::Token(token),
+                ..Self::default()
+            }
+        }
+
         /// Deposit funds into a vesting schedule.
         ///
         /// # Arguments
         ///
         /// * `beneficiary`: The account that will receive the vested funds.
-        /// * `unlock_time`: The timestamp when the funds will be unlocked.
+        /// * `unlock_time`: The timestamp when the funds will be unlocked. The funds can be
+        ///   withdrawn in the block whose timestamp equals `unlock_time`.
+        ///
+        /// Returns the ID of the new schedule.
         ///
         /// # Errors
         ///
         /// Returns `Error::ZeroAmount` if the deposited amount is zero.
+        /// Returns `Error::AmountTooLarge` if the amount exceeds the maximum schedule amount.
+        /// Returns `Error::UnlockInPast` if the funds would be fully vested already.
+        /// Returns `Error::InvalidUnlockTime` if the unlock time is zero.
+        /// Returns `Error::UnlockTooFar` if the funds would vest beyond the allowed horizon.
         /// Returns `Error::IdOverflow` if the schedule ID counter overflows.
+        /// Returns `Error::AmountOverflow` if the locked totals overflow.
+        /// Returns `Error::Paused` if the contract is paused.
+        /// Returns `Error::DepositsFrozen` if new deposits are frozen.
+        /// Returns `Error::NotWhitelisted` if the whitelist is enabled and the caller is not on it.
+        /// Returns `Error::InvalidBeneficiary` if the beneficiary is the zero address or the contract itself.
+        /// Returns `Error::TooManySchedules` if the beneficiary already has the maximum number of schedules.
+        /// Returns `Error::WrongMode` if the contract vests a PSP22 token.
         #[ink(message, payable)]
         pub fn deposit_fund(
             &mut self,
             beneficiary: AccountId,
             unlock_time: Timestamp
-        ) -> Result<()> {
-            // Get the caller and transferred amount
-            let owner = self.env().caller();
-            let amount = self.env().transferred_value();
-
-            // Prevent zero-value deposits
-            if amount == 0 {
-                return Err(Error::ZeroAmount);
-            }
-
-            // Generate new schedule ID with overflow check
-            // Without this check, if id reaches 18,446,744,073,709,551,615 (u64::MAX)
-            // Adding 1 would wrap to 0 (integer overflow)
-            let id = self.id;
-            self.id = id.checked_add(1).ok_or(Error::IdOverflow)?;
-
-            // Create new vesting schedule
-            let schedule = VestingSchedule {
-                owner,
-                beneficiary,
-                amount,
-                unlock_time,
-            };
-
-            // Store the schedule
-            self.schedules.insert(id, &schedule);
-
-            // Update beneficiary's schedule list
-            let mut ids = self.beneficiary_to_ids.get(beneficiary).unwrap_or_default();
-            ids.push(id);
-            self.beneficiary_to_ids.insert(beneficiary, &ids);
-
-            Ok(())
+        ) -> Result<u64> {
+            let amount = self.transferred_native()?;
+            self.create_schedule(beneficiary, amount, VestingKind::Cliff { unlock_time }, None, true, false)
         }
 
-        /// Withdraw all available vested funds for the caller.
+        /// Deposit funds into a vesting schedule that the owner can never revoke.
+        ///
+        /// # Arguments
+        ///
+        /// * `beneficiary`: The account that will receive the vested funds.
+        /// * `unlock_time`: The timestamp when the funds will be unlocked.
+        ///
+        /// Returns the ID of the new schedule.
         ///
         /// # Errors
         ///
-        /// Returns `Error::NoFundsAvailable` if no funds are available for withdrawal.
-        /// Returns `Error::TransferFailed` if the token transfer fails.
-        #[ink(message)]
-        pub fn withdraw_fund(&mut self) -> Result<()> {
-            // Get caller and current block time
-            let beneficiary = self.env().caller();
-            let current_time: Timestamp = self.env().block_timestamp();
-
-            // Retrieve all schedule IDs for beneficiary
-            let ids = self.beneficiary_to_ids.get(beneficiary).unwrap_or_default();
-            let mut total_amount: u128 = 0;
-            let mut remaining_ids = Vec::new();
-
-            // Process each schedule
-            for &id in &ids {
-                if let Some(schedule) = self.schedules.get(id) {
-                    if schedule.unlock_time <= current_time {
-                        // Add to total if unlocked, remove schedule
-                        total_amount = total_amount
-                            .checked_add(schedule.amount)
-                            .ok_or(Error::TransferFailed)?;
-                        self.schedules.remove(id);
-                    } else {
-                        // Keep locked schedules
-                        remaining_ids.push(id);
-                    }
-                }
-            }
-
-            // Check if any funds are available
-            if total_amount == 0 {
-                return Err(Error::NoFundsAvailable);
-            }
-
-            // Update remaining schedule IDs
-            self.beneficiary_to_ids.insert(beneficiary, &remaining_ids);
-
-            // Transfer funds to beneficiary
-            self
-                .env()
-                .transfer(beneficiary, total_amount)
-                .map_err(|_| Error::TransferFailed)?;
-
-            Ok(())
+        /// Returns `Error::ZeroAmount` if the deposited amount is zero.
+        /// Returns `Error::AmountTooLarge` if the amount exceeds the maximum schedule amount.
+        /// Returns `Error::UnlockInPast` if the funds would be fully vested already.
+        /// Returns `Error::InvalidUnlockTime` if the unlock time is zero.
+        /// Returns `Error::UnlockTooFar` if the funds would vest beyond the allowed horizon.
+        /// Returns `Error::IdOverflow` if the schedule ID counter overflows.
+        /// Returns `Error::AmountOverflow` if the locked totals overflow.
+        /// Returns `Error::Paused` if the contract is paused.
+        /// Returns `Error::DepositsFrozen` if new deposits are frozen.
+        /// Returns `Error::NotWhitelisted` if the whitelist is enabled and the caller is not on it.
+        /// Returns `Error::InvalidBeneficiary` if the beneficiary is the zero address or the contract itself.
+        /// Returns `Error::TooManySchedules` if the beneficiary already has the maximum number of schedules.
+        /// Returns `Error::WrongMode` if the contract vests a PSP22 token.
+        #[ink(message, payable)]
+        pub fn deposit_fund_irrevocable(
+            &mut self,
+            beneficiary: AccountId,
+            unlock_time: Timestamp
+        ) -> Result<u64> {
+            let amount = self.transferred_native()?;
+            self.create_schedule(beneficiary, amount, VestingKind::Cliff { unlock_time }, None, false, false)
         }
-    }
 
-    //----------------------------------
-    // Testing Framework
-    //----------------------------------
-    #[cfg(test)]
-    mod tests {
-        use super::*;
-        use ink::env::{
-            test::{
+        /// Deposit funds into a vesting schedule the beneficiary has to accept first.
+        ///
+        /// Until the beneficiary calls `accept_schedule`, nothing can be withdrawn and the
+        /// owner can revoke the schedule even if it has vested.
+        ///
+        /// # Arguments
+        ///
+        /// * `beneficiary`: The account that will receive the vested funds.
+        /// * `unlock_time`: The timestamp when the funds will be unlocked.
+        ///
+        /// Returns the ID of the new schedule.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::ZeroAmount` if the deposited amount is zero.
+        /// Returns `Error::AmountTooLarge` if the amount exceeds the maximum schedule amount.
+        /// Returns `Error::UnlockInPast` if the funds would be fully vested already.
+        /// Returns `Error::InvalidUnlockTime` if the unlock time is zero.
+        /// Returns `Error::UnlockTooFar` if the funds would vest beyond the allowed horizon.
+        /// Returns `Error::IdOverflow` if the schedule ID counter overflows.
+        /// Returns `Error::AmountOverflow` if the locked totals overflow.
+        /// Returns `Error::Paused` if the contract is paused.
+        /// Returns `Error::DepositsFrozen` if new deposits are frozen.
+        /// Returns `Error::NotWhitelisted` if the whitelist is enabled and the caller is not on it.
+        /// Returns `Error::InvalidBeneficiary` if the beneficiary is the zero address or the contract itself.
+        /// Returns `Error::TooManySchedules` if the beneficiary already has the maximum number of schedules.
+        /// Returns `Error::WrongMode` if the contract vests a PSP22 token.
+        #[ink(message, payable)]
+        pub fn deposit_fund_with_acceptance(
+            &mut self,
+            beneficiary: AccountId,
+            unlock_time: Timestamp
+        ) -> Result<u64> {
+            let amount = self.transferred_native()?;
+            self.create_schedule(beneficiary, amount, VestingKind::Cliff { unlock_time }, None, true, true)
+        }
+
+        /// Deposit funds into a vesting schedule annotated with a label.
+        ///
+        /// # Arguments
+        ///
+        /// * `beneficiary`: The account that will receive the vested funds.
+        /// * `unlock_time`: The timestamp when the funds will be unlocked.
+        /// * `label`: A note of at most 64 bytes, e.g. "advisor grant".
+        ///
+        /// Returns the ID of the new schedule.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::LabelTooLong` if `label` is longer than 64 bytes.
+        /// Returns `Error::ZeroAmount` if the deposited amount is zero.
+        /// Returns `Error::AmountTooLarge` if the amount exceeds the maximum schedule amount.
+        /// Returns `Error::UnlockInPast` if the funds would be fully vested already.
+        /// Returns `Error::InvalidUnlockTime` if the unlock time is zero.
+        /// Returns `Error::UnlockTooFar` if the funds would vest beyond the allowed horizon.
+        /// Returns `Error::IdOverflow` if the schedule ID counter overflows.
+        /// Returns `Error::AmountOverflow` if the locked totals overflow.
+        /// Returns `Error::Paused` if the contract is paused.
+        /// Returns `Error::DepositsFrozen` if new deposits are frozen.
+        /// Returns `Error::NotWhitelisted` if the whitelist is enabled and the caller is not on it.
+        /// Returns `Error::InvalidBeneficiary` if the beneficiary is the zero address or the contract itself.
+        /// Returns `Error::TooManySchedules` if the beneficiary already has the maximum number of schedules.
+        /// Returns `Error::WrongMode` if the contract vests a PSP22 token.
+        #[ink(message, payable)]
+        pub fn deposit_fund_labeled(
+            &mut self,
+            beneficiary: AccountId,
+            unlock_time: Timestamp,
+            label: Vec<u8>
+        ) -> Result<u64> {
+            if label.len() > MAX_LABEL_LEN {
+                return Err(Error::LabelTooLong);
+            }
+
+            let amount = self.transferred_native()?;
+            let kind = VestingKind::Cliff { unlock_time };
+            self.create_schedule(beneficiary, amount, kind, Some(label), true, false)
+        }
+
+        /// Deposit an explicit amount into a vesting schedule, refunding the caller any
+        /// value transferred on top of it.
+        ///
+        /// # Arguments
+        ///
+        /// * `beneficiary`: The account that will receive the vested funds.
+        /// * `unlock_time`: The timestamp when the funds will be unlocked.
+        /// * `amount`: The amount to lock, at most the transferred value.
+        ///
+        /// Returns the ID of the new schedule.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::InsufficientValue` if the transferred value is below `amount`.
+        /// Returns `Error::ZeroAmount` if `amount` is zero.
+        /// Returns `Error::AmountTooLarge` if the amount exceeds the maximum schedule amount.
+        /// Returns `Error::UnlockInPast` if the funds would be fully vested already.
+        /// Returns `Error::InvalidUnlockTime` if the unlock time is zero.
+        /// Returns `Error::UnlockTooFar` if the funds would vest beyond the allowed horizon.
+        /// Returns `Error::IdOverflow` if the schedule ID counter overflows.
+        /// Returns `Error::AmountOverflow` if the locked totals overflow.
+        /// Returns `Error::Paused` if the contract is paused.
+        /// Returns `Error::DepositsFrozen` if new deposits are frozen.
+        /// Returns `Error::NotWhitelisted` if the whitelist is enabled and the caller is not on it.
+        /// Returns `Error::InvalidBeneficiary` if the beneficiary is the zero address or the contract itself.
+        /// Returns `Error::TooManySchedules` if the beneficiary already has the maximum number of schedules.
+        /// Returns `Error::TransferFailed` if the surplus cannot be refunded.
+        /// Returns `Error::WrongMode` if the contract vests a PSP22 token.
+        #[ink(message, payable)]
+        pub fn deposit_fund_exact(
+            &mut self,
+            beneficiary: AccountId,
+            unlock_time: Timestamp,
+            amount: Balance
+        ) -> Result<u64> {
+            let transferred = self.transferred_native()?;
+            if transferred < amount {
+                return Err(Error::InsufficientValue);
+            }
+
+            let kind = VestingKind::Cliff { unlock_time };
+            let id = self.create_schedule(beneficiary, amount, kind, None, true, false)?;
+
+            let surplus = transferred - amount;
+            if surplus > 0 {
+                self.env()
+                    .transfer(self.env().caller(), surplus)
+                    .map_err(|_| Error::TransferFailed)?;
+            }
+
+            Ok(id)
+        }
+
+        /// Deposit funds into a vesting schedule that unlocks a given time from now.
+        ///
+        /// # Arguments
+        ///
+        /// * `beneficiary`: The account that will receive the vested funds.
+        /// * `duration_ms`: The time in milliseconds, from the current block, after which
+        ///   the funds are unlocked.
+        ///
+        /// Returns the ID of the new schedule.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::ZeroAmount` if the deposited amount is zero.
+        /// Returns `Error::AmountTooLarge` if the amount exceeds the maximum schedule amount.
+        /// Returns `Error::UnlockInPast` if `duration_ms` is zero.
+        /// Returns `Error::UnlockTooFar` if the unlock time overflows or is beyond the allowed horizon.
+        /// Returns `Error::IdOverflow` if the schedule ID counter overflows.
+        /// Returns `Error::AmountOverflow` if the locked totals overflow.
+        /// Returns `Error::Paused` if the contract is paused.
+        /// Returns `Error::DepositsFrozen` if new deposits are frozen.
+        /// Returns `Error::NotWhitelisted` if the whitelist is enabled and the caller is not on it.
+        /// Returns `Error::InvalidBeneficiary` if the beneficiary is the zero address or the contract itself.
+        /// Returns `Error::TooManySchedules` if the beneficiary already has the maximum number of schedules.
+        /// Returns `Error::WrongMode` if the contract vests a PSP22 token.
+        #[ink(message, payable)]
+        pub fn deposit_fund_after(&mut self, beneficiary: AccountId, duration_ms: u64) -> Result<u64> {
+            let unlock_time = self
+                .env()
+                .block_timestamp()
+                .checked_add(duration_ms)
+                .ok_or(Error::UnlockTooFar)?;
+            let amount = self.transferred_native()?;
+            self.create_schedule(beneficiary, amount, VestingKind::Cliff { unlock_time }, None, true, false)
+        }
+
+        /// Deposit PSP22 tokens into a vesting schedule.
+        ///
+        /// The caller must have approved the contract to spend `amount` tokens beforehand.
+        ///
+        /// # Arguments
+        ///
+        /// * `beneficiary`: The account that will receive the vested tokens.
+        /// * `unlock_time`: The timestamp when the tokens will be unlocked.
+        /// * `amount`: The amount of tokens to vest.
+        ///
+        /// Returns the ID of the new schedule.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::ZeroAmount` if `amount` is zero.
+        /// Returns `Error::AmountTooLarge` if the amount exceeds the maximum schedule amount.
+        /// Returns `Error::UnlockInPast` if the funds would be fully vested already.
+        /// Returns `Error::InvalidUnlockTime` if the unlock time is zero.
+        /// Returns `Error::UnlockTooFar` if the funds would vest beyond the allowed horizon.
+        /// Returns `Error::IdOverflow` if the schedule ID counter overflows.
+        /// Returns `Error::AmountOverflow` if the locked totals overflow.
+        /// Returns `Error::WrongMode` if the contract vests the native balance.
+        /// Returns `Error::TokenCallFailed` if the `transfer_from` call fails.
+        /// Returns `Error::Paused` if the contract is paused.
+        /// Returns `Error::DepositsFrozen` if new deposits are frozen.
+        /// Returns `Error::NotWhitelisted` if the whitelist is enabled and the caller is not on it.
+        /// Returns `Error::InvalidBeneficiary` if the beneficiary is the zero address or the contract itself.
+        /// Returns `Error::TooManySchedules` if the beneficiary already has the maximum number of schedules.
+        #[ink(message)]
+        pub fn deposit_fund_token(
+            &mut self,
+            beneficiary: AccountId,
+            unlock_time: Timestamp,
+            amount: Balance
+        ) -> Result<u64> {
+            let VestingMode::Token(token) = self.mode else {
+                return Err(Error::WrongMode);
+            };
+
+            // Check the schedule before calling the token
+            let kind = VestingKind::Cliff { unlock_time };
+            self.ensure_valid_schedule(beneficiary, amount, &kind)?;
+
+            // Pull the tokens from the caller into the contract
+            let from = self.env().caller();
+            let to = self.env().account_id();
+            self.call_token(token, TokenCall::TransferFrom { from, to, value: amount })?;
+
+            self.record_schedule(beneficiary, amount, kind, None, true, false)
+        }
+
+        /// Deposit funds that unlock at a block number instead of a timestamp.
+        ///
+        /// The full amount becomes available once the chain reaches `unlock_block`.
+        /// The unlock horizon, being a duration, does not apply to these schedules.
+        ///
+        /// # Arguments
+        ///
+        /// * `beneficiary`: The account that will receive the vested funds.
+        /// * `unlock_block`: The block number when the funds become available.
+        ///
+        /// Returns the ID of the new schedule.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::ZeroAmount` if the deposited amount is zero.
+        /// Returns `Error::AmountTooLarge` if the amount exceeds the maximum schedule amount.
+        /// Returns `Error::UnlockInPast` if `unlock_block` is not after the current block.
+        /// Returns `Error::InvalidUnlockTime` if `unlock_block` is zero.
+        /// Returns `Error::IdOverflow` if the schedule ID counter overflows.
+        /// Returns `Error::AmountOverflow` if the locked totals overflow.
+        /// Returns `Error::Paused` if the contract is paused.
+        /// Returns `Error::DepositsFrozen` if new deposits are frozen.
+        /// Returns `Error::NotWhitelisted` if the whitelist is enabled and the caller is not on it.
+        /// Returns `Error::InvalidBeneficiary` if the beneficiary is the zero address or the contract itself.
+        /// Returns `Error::TooManySchedules` if the beneficiary already has the maximum number of schedules.
+        /// Returns `Error::WrongMode` if the contract vests a PSP22 token.
+        #[ink(message, payable)]
+        pub fn deposit_fund_by_block(
+            &mut self,
+            beneficiary: AccountId,
+            unlock_block: BlockNumber
+        ) -> Result<u64> {
+            let amount = self.transferred_native()?;
+            self.create_schedule(beneficiary, amount, VestingKind::Block { unlock_block }, None, true, false)
+        }
+
+        /// Deposit funds into a linearly vesting schedule.
+        ///
+        /// The funds vest proportionally between `start_time` and `start_time + duration`,
+        /// and the beneficiary can withdraw the vested portion at any time.
+        ///
+        /// # Arguments
+        ///
+        /// * `beneficiary`: The account that will receive the vested funds.
+        /// * `start_time`: The timestamp when the funds start vesting.
+        /// * `duration`: The time (in milliseconds) it takes for the full amount to vest.
+        ///
+        /// Returns the ID of the new schedule.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::ZeroAmount` if the deposited amount is zero.
+        /// Returns `Error::AmountTooLarge` if the amount exceeds the maximum schedule amount.
+        /// Returns `Error::UnlockInPast` if the funds would be fully vested already.
+        /// Returns `Error::InvalidUnlockTime` if the unlock time is zero.
+        /// Returns `Error::UnlockTooFar` if the funds would vest beyond the allowed horizon.
+        /// Returns `Error::IdOverflow` if the schedule ID counter overflows.
+        /// Returns `Error::AmountOverflow` if the locked totals overflow.
+        /// Returns `Error::Paused` if the contract is paused.
+        /// Returns `Error::DepositsFrozen` if new deposits are frozen.
+        /// Returns `Error::NotWhitelisted` if the whitelist is enabled and the caller is not on it.
+        /// Returns `Error::InvalidBeneficiary` if the beneficiary is the zero address or the contract itself.
+        /// Returns `Error::TooManySchedules` if the beneficiary already has the maximum number of schedules.
+        /// Returns `Error::WrongMode` if the contract vests a PSP22 token.
+        #[ink(message, payable)]
+        pub fn deposit_fund_linear(
+            &mut self,
+            beneficiary: AccountId,
+            start_time: Timestamp,
+            duration: Timestamp
+        ) -> Result<u64> {
+            let amount = self.transferred_native()?;
+            let kind = VestingKind::Linear { start_time, duration };
+            self.create_schedule(beneficiary, amount, kind, None, true, false)
+        }
+
+        /// Deposit funds into a schedule that vests linearly after a cliff.
+        ///
+        /// Nothing can be withdrawn before `cliff_time`; from then on the funds vest
+        /// proportionally until the full amount is available at `end_time`.
+        ///
+        /// # Arguments
+        ///
+        /// * `beneficiary`: The account that will receive the vested funds.
+        /// * `cliff_time`: The timestamp when the funds start vesting.
+        /// * `end_time`: The timestamp when the full amount is vested.
+        ///
+        /// Returns the ID of the new schedule.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::InvalidSchedule` if `cliff_time` is not before `end_time`.
+        /// Returns `Error::ZeroAmount` if the deposited amount is zero.
+        /// Returns `Error::AmountTooLarge` if the amount exceeds the maximum schedule amount.
+        /// Returns `Error::UnlockInPast` if the funds would be fully vested already.
+        /// Returns `Error::InvalidUnlockTime` if the unlock time is zero.
+        /// Returns `Error::UnlockTooFar` if the funds would vest beyond the allowed horizon.
+        /// Returns `Error::IdOverflow` if the schedule ID counter overflows.
+        /// Returns `Error::AmountOverflow` if the locked totals overflow.
+        /// Returns `Error::Paused` if the contract is paused.
+        /// Returns `Error::DepositsFrozen` if new deposits are frozen.
+        /// Returns `Error::NotWhitelisted` if the whitelist is enabled and the caller is not on it.
+        /// Returns `Error::InvalidBeneficiary` if the beneficiary is the zero address or the contract itself.
+        /// Returns `Error::TooManySchedules` if the beneficiary already has the maximum number of schedules.
+        /// Returns `Error::WrongMode` if the contract vests a PSP22 token.
+        #[ink(message, payable)]
+        pub fn deposit_fund_cliff_linear(
+            &mut self,
+            beneficiary: AccountId,
+            cliff_time: Timestamp,
+            end_time: Timestamp
+        ) -> Result<u64> {
+            if cliff_time >= end_time {
+                return Err(Error::InvalidSchedule);
+            }
+
+            // A linear schedule starting at the cliff releases nothing before it
+            let amount = self.transferred_native()?;
+            let kind = VestingKind::Linear {
+                start_time: cliff_time,
+                duration: end_time - cliff_time,
+            };
+            self.create_schedule(beneficiary, amount, kind, None, true, false)
+        }
+
+        /// Deposit funds into a schedule that releases part of them right away, at the
+        /// token generation event (TGE), and vests the rest linearly.
+        ///
+        /// `tge_bps` basis points of the deposit are claimable immediately; the remainder
+        /// vests proportionally from `start_time` over `duration`.
+        ///
+        /// # Arguments
+        ///
+        /// * `beneficiary`: The account that will receive the vested funds.
+        /// * `tge_bps`: The share of the deposit released at once, in basis points.
+        /// * `start_time`: The timestamp when the remainder starts vesting.
+        /// * `duration`: The time (in milliseconds) it takes for the remainder to vest.
+        ///
+        /// Returns the ID of the new schedule.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::InvalidSchedule` if `tge_bps` is above 10000.
+        /// Returns `Error::ZeroAmount` if the deposited amount is zero.
+        /// Returns `Error::AmountTooLarge` if the amount exceeds the maximum schedule amount.
+        /// Returns `Error::UnlockInPast` if the funds would be fully vested already.
+        /// Returns `Error::InvalidUnlockTime` if the unlock time is zero.
+        /// Returns `Error::UnlockTooFar` if the funds would vest beyond the allowed horizon.
+        /// Returns `Error::IdOverflow` if the schedule ID counter overflows.
+        /// Returns `Error::AmountOverflow` if the locked totals overflow.
+        /// Returns `Error::Paused` if the contract is paused.
+        /// Returns `Error::DepositsFrozen` if new deposits are frozen.
+        /// Returns `Error::NotWhitelisted` if the whitelist is enabled and the caller is not on it.
+        /// Returns `Error::InvalidBeneficiary` if the beneficiary is the zero address or the contract itself.
+        /// Returns `Error::TooManySchedules` if the beneficiary already has the maximum number of schedules.
+        /// Returns `Error::WrongMode` if the contract vests a PSP22 token.
+        #[ink(message, payable)]
+        pub fn deposit_fund_tge(
+            &mut self,
+            beneficiary: AccountId,
+            tge_bps: u16,
+            start_time: Timestamp,
+            duration: Timestamp
+        ) -> Result<u64> {
+            if tge_bps > 10_000 {
+                return Err(Error::InvalidSchedule);
+            }
+
+            let amount = self.transferred_native()?;
+            let kind = VestingKind::LinearWithTge {
+                tge_amount: bps_of(amount, tge_bps),
+                start_time,
+                duration,
+            };
+            self.create_schedule(beneficiary, amount, kind, None, true, false)
+        }
+
+        /// Deposit funds into a schedule released in several tranches.
+        ///
+        /// Each tranche becomes available at its own time, so periodic releases can
+        /// be covered by a single schedule.
+        ///
+        /// # Arguments
+        ///
+        /// * `beneficiary`: The account that will receive the vested funds.
+        /// * `unlocks`: The `(unlock_time, amount)` of each tranche, in strictly increasing time order.
+        ///
+        /// Returns the ID of the new schedule.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::InvalidSchedule` if there are no tranches or their times are not
+        /// strictly increasing.
+        /// Returns `Error::ZeroAmount` if any tranche amount is zero.
+        /// Returns `Error::AmountTooLarge` if the amount exceeds the maximum schedule amount.
+        /// Returns `Error::AmountOverflow` if the amounts overflow when added up.
+        /// Returns `Error::AmountMismatch` if the amounts do not add up to the transferred value.
+        /// Returns `Error::UnlockInPast` if the last tranche would be available already.
+        /// Returns `Error::InvalidUnlockTime` if the unlock time is zero.
+        /// Returns `Error::UnlockTooFar` if the last tranche would vest beyond the allowed horizon.
+        /// Returns `Error::IdOverflow` if the schedule ID counter overflows.
+        /// Returns `Error::Paused` if the contract is paused.
+        /// Returns `Error::DepositsFrozen` if new deposits are frozen.
+        /// Returns `Error::NotWhitelisted` if the whitelist is enabled and the caller is not on it.
+        /// Returns `Error::InvalidBeneficiary` if the beneficiary is the zero address or the contract itself.
+        /// Returns `Error::TooManySchedules` if the beneficiary already has the maximum number of schedules.
+        /// Returns `Error::WrongMode` if the contract vests a PSP22 token.
+        #[ink(message, payable)]
+        pub fn deposit_fund_tranches(
+            &mut self,
+            beneficiary: AccountId,
+            unlocks: Vec<(Timestamp, Balance)>
+        ) -> Result<u64> {
+            if unlocks.is_empty() || unlocks.windows(2).any(|pair| pair[0].0 >= pair[1].0) {
+                return Err(Error::InvalidSchedule);
+            }
+            if unlocks.iter().any(|&(_, amount)| amount == 0) {
+                return Err(Error::ZeroAmount);
+            }
+
+            // The tranches must account for exactly the transferred value
+            let amount = unlocks
+                .iter()
+                .try_fold(0u128, |total, &(_, amount)| total.checked_add(amount))
+                .ok_or(Error::AmountOverflow)?;
+            if amount != self.transferred_native()? {
+                return Err(Error::AmountMismatch);
+            }
+
+            let kind = VestingKind::Tranches { tranches: unlocks };
+            self.create_schedule(beneficiary, amount, kind, None, true, false)
+        }
+
+        /// Deposit funds into several vesting schedules at once.
+        ///
+        /// The transferred value is split across the entries according to their amounts.
+        ///
+        /// # Arguments
+        ///
+        /// * `entries`: The `(beneficiary, unlock_time, amount)` of each schedule to create.
+        ///
+        /// Returns the IDs of the new schedules, in the order of `entries`.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::AmountOverflow` if the amounts overflow when added up.
+        /// Returns `Error::AmountMismatch` if the amounts do not add up to the transferred value.
+        /// Returns `Error::ZeroAmount` if any amount is zero.
+        /// Returns `Error::AmountTooLarge` if the amount exceeds the maximum schedule amount.
+        /// Returns `Error::UnlockInPast` if any funds would be fully vested already.
+        /// Returns `Error::InvalidUnlockTime` if the unlock time is zero.
+        /// Returns `Error::UnlockTooFar` if any funds would vest beyond the allowed horizon.
+        /// Returns `Error::IdOverflow` if the schedule ID counter overflows.
+        /// Returns `Error::Paused` if the contract is paused.
+        /// Returns `Error::DepositsFrozen` if new deposits are frozen.
+        /// Returns `Error::NotWhitelisted` if the whitelist is enabled and the caller is not on it.
+        /// Returns `Error::InvalidBeneficiary` if the beneficiary is the zero address or the contract itself.
+        /// Returns `Error::TooManySchedules` if the beneficiary already has the maximum number of schedules.
+        /// Returns `Error::WrongMode` if the contract vests a PSP22 token.
+        #[ink(message, payable)]
+        pub fn deposit_fund_batch(
+            &mut self,
+            entries: Vec<(AccountId, Timestamp, Balance)>
+        ) -> Result<Vec<u64>> {
+            self.create_schedules(entries)
+        }
+
+        /// Add the transferred value to an existing schedule that has not started vesting.
+        ///
+        /// # Arguments
+        ///
+        /// * `id`: The ID of the schedule to top up.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::ZeroAmount` if the deposited amount is zero.
+        /// Returns `Error::AmountTooLarge` if the amount exceeds the maximum schedule amount.
+        /// Returns `Error::ScheduleNotFound` if no schedule exists for `id`.
+        /// Returns `Error::NotOwner` if the caller did not create the schedule.
+        /// Returns `Error::AlreadyVested` if any part of the schedule has already vested.
+        /// Returns `Error::AmountOverflow` if the new total amount of the schedule overflows.
+        /// Returns `Error::Paused` if the contract is paused.
+        /// Returns `Error::DepositsFrozen` if new deposits are frozen.
+        /// Returns `Error::NotWhitelisted` if the whitelist is enabled and the caller is not on it.
+        /// Returns `Error::WrongMode` if the contract vests a PSP22 token.
+        #[ink(message, payable)]
+        pub fn top_up(&mut self, id: u64) -> Result<()> {
+            self.ensure_deposits_open()?;
+
+            let caller = self.env().caller();
+            self.ensure_whitelisted(caller)?;
+            let amount = self.transferred_native()?;
+            let current_time: Timestamp = self.env().block_timestamp();
+            let current_block = self.env().block_number();
+
+            // Prevent zero-value top-ups
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+
+            let mut schedule = self.load_schedule(id).ok_or(Error::ScheduleNotFound)?;
+
+            // Only the creator of the schedule can add to it
+            if caller != schedule.owner {
+                return Err(Error::NotOwner);
+            }
+
+            // Adding to a vesting schedule would change what is already claimable
+            if schedule.vested_amount(current_time, current_block) > 0 {
+                return Err(Error::AlreadyVested);
+            }
+
+            // The tranches fix the amount released at each time, there is nothing to extend
+            if let VestingKind::Tranches { .. } = schedule.kind {
+                return Err(Error::InvalidSchedule);
+            }
+
+            schedule.amount = schedule.amount.checked_add(amount).ok_or(Error::AmountOverflow)?;
+            self.ensure_schedule_amount(schedule.amount)?;
+            self.lock_funds(schedule.beneficiary, amount)?;
+            self.store_schedule(id, &schedule);
+
+            self.env().emit_event(ToppedUp {
+                id,
+                amount,
+                new_amount: schedule.amount,
+            });
+
+            Ok(())
+        }
+
+        /// Postpone the unlock time of a cliff schedule. The unlock time can never be
+        /// brought forward.
+        ///
+        /// # Arguments
+        ///
+        /// * `id`: The ID of the schedule to extend.
+        /// * `new_unlock_time`: The new unlock time, later than the current one.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::ScheduleNotFound` if no schedule exists for `id`.
+        /// Returns `Error::NotOwner` if the caller did not create the schedule.
+        /// Returns `Error::InvalidSchedule` if the schedule is not a cliff schedule.
+        /// Returns `Error::AlreadyVested` if the schedule is already unlocked.
+        /// Returns `Error::CannotShorten` if `new_unlock_time` is not later than the current unlock time.
+        /// Returns `Error::UnlockTooFar` if the funds would vest beyond the allowed horizon.
+        /// Returns `Error::Paused` if the contract is paused.
+        #[ink(message)]
+        pub fn extend_unlock(&mut self, id: u64, new_unlock_time: Timestamp) -> Result<()> {
+            self.ensure_not_paused()?;
+            let caller = self.env().caller();
+            let current_time: Timestamp = self.env().block_timestamp();
+
+            let mut schedule = self.load_schedule(id).ok_or(Error::ScheduleNotFound)?;
+
+            // Only the creator of the schedule can extend it
+            if caller != schedule.owner {
+                return Err(Error::NotOwner);
+            }
+
+            // Postponing a linear or tranche schedule would lower what is already vested
+            let VestingKind::Cliff { unlock_time } = schedule.kind else {
+                return Err(Error::InvalidSchedule);
+            };
+
+            // Funds the beneficiary can already claim must not be locked again
+            if unlock_time <= current_time {
+                return Err(Error::AlreadyVested);
+            }
+            if new_unlock_time <= unlock_time {
+                return Err(Error::CannotShorten);
+            }
+            if let Some(max_unlock_horizon) = self.max_unlock_horizon {
+                if new_unlock_time - current_time > max_unlock_horizon {
+                    return Err(Error::UnlockTooFar);
+                }
+            }
+
+            schedule.kind = VestingKind::Cliff { unlock_time: new_unlock_time };
+            self.store_schedule(id, &schedule);
+
+            self.env().emit_event(UnlockExtended { id, new_unlock_time });
+
+            Ok(())
+        }
+
+        /// Split part of a locked cliff schedule off into a new schedule with the same
+        /// beneficiary and unlock time, e.g. to reassign it separately.
+        ///
+        /// # Arguments
+        ///
+        /// * `id`: The ID of the schedule to split.
+        /// * `amount`: The amount moved to the new schedule.
+        ///
+        /// Returns the ID of the new schedule.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::ScheduleNotFound` if no schedule exists for `id`.
+        /// Returns `Error::NotOwner` if the caller did not create the schedule.
+        /// Returns `Error::InvalidSchedule` if the schedule is not a cliff schedule.
+        /// Returns `Error::AlreadyVested` if the schedule is already unlocked.
+        /// Returns `Error::ZeroAmount` if `amount` is zero.
+        /// Returns `Error::InvalidSplit` if `amount` is not less than the schedule amount.
+        /// Returns `Error::RevokeNotApproved` if the schedule is above the large amount threshold
+        /// and the co-signer has not approved its revocation.
+        /// Returns `Error::TooManySchedules` if the beneficiary already has the maximum number of schedules.
+        /// Returns `Error::IdOverflow` if the schedule ID counter overflows.
+        /// Returns `Error::Paused` if the contract is paused.
+        #[ink(message)]
+        pub fn split_schedule(&mut self, id: u64, amount: Balance) -> Result<u64> {
+            self.ensure_not_paused()?;
+            let caller = self.env().caller();
+            let current_time: Timestamp = self.env().block_timestamp();
+
+            let mut schedule = self.load_schedule(id).ok_or(Error::ScheduleNotFound)?;
+
+            // Only the creator of the schedule can split it
+            if caller != schedule.owner {
+                return Err(Error::NotOwner);
+            }
+
+            // Linear and tranche schedules cannot be split without re-cutting their curve
+            let VestingKind::Cliff { unlock_time } = schedule.kind else {
+                return Err(Error::InvalidSchedule);
+            };
+            if unlock_time <= current_time {
+                return Err(Error::AlreadyVested);
+            }
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            if amount >= schedule.amount {
+                return Err(Error::InvalidSplit);
+            }
+            // Pieces below the threshold could otherwise be revoked without approval
+            self.ensure_revoke_approved(id, schedule.amount)?;
+            self.ensure_schedule_capacity(schedule.beneficiary)?;
+
+            // Move the funds, the total locked for the beneficiary stays the same
+            schedule.amount -= amount;
+            self.store_schedule(id, &schedule);
+            self.unlock_funds(schedule.beneficiary, amount);
+            let new_id = self.insert_schedule(VestingSchedule { amount, ..schedule })?;
+
+            self.env().emit_event(Split { id, new_id, amount });
+
+            Ok(new_id)
+        }
+
+        /// Merge cliff schedules with the same beneficiary and unlock time into a single
+        /// new schedule holding their combined amount, e.g. to clean up fragmented grants.
+        /// The original schedules are removed. The label of the first schedule is kept.
+        ///
+        /// # Arguments
+        ///
+        /// * `ids`: The IDs of the schedules to merge, at least two.
+        ///
+        /// Returns the ID of the new schedule.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::CannotMerge` if fewer than two distinct schedules are given, or if
+        /// their beneficiary, unlock time, revocability or acceptance differ, or if one was
+        /// partially withdrawn.
+        /// Returns `Error::ScheduleNotFound` if no schedule exists for one of `ids`.
+        /// Returns `Error::NotOwner` if the caller did not create one of the schedules.
+        /// Returns `Error::InvalidSchedule` if one of the schedules is not a cliff schedule.
+        /// Returns `Error::AmountOverflow` if the combined amount overflows.
+        /// Returns `Error::AmountTooLarge` if the combined amount exceeds the maximum schedule amount.
+        /// Returns `Error::IdOverflow` if the schedule ID counter overflows.
+        /// Returns `Error::Paused` if the contract is paused.
+        #[ink(message)]
+        pub fn merge_schedules(&mut self, ids: Vec<u64>) -> Result<u64> {
+            self.ensure_not_paused()?;
+            let caller = self.env().caller();
+
+            let unique_ids: BTreeSet<u64> = ids.iter().copied().collect();
+            if ids.len() < 2 || unique_ids.len() != ids.len() {
+                return Err(Error::CannotMerge);
+            }
+
+            let mut schedules = Vec::new();
+            for &id in &ids {
+                let schedule = self.load_schedule(id).ok_or(Error::ScheduleNotFound)?;
+                // Only the creator of the schedules can merge them
+                if caller != schedule.owner {
+                    return Err(Error::NotOwner);
+                }
+                if !matches!(schedule.kind, VestingKind::Cliff { .. }) {
+                    return Err(Error::InvalidSchedule);
+                }
+                schedules.push(schedule);
+            }
+
+            // Every schedule must share the terms of the first one
+            let first = schedules[0].clone();
+            let mut amount: Balance = 0;
+            for schedule in &schedules {
+                let same_terms = schedule.beneficiary == first.beneficiary && schedule.kind == first.kind;
+                let same_flags = schedule.revocable == first.revocable
+                    && schedule.pending_acceptance == first.pending_acceptance;
+                if !same_terms || !same_flags || schedule.released > 0 {
+                    return Err(Error::CannotMerge);
+                }
+                amount = amount.checked_add(schedule.amount).ok_or(Error::AmountOverflow)?;
+            }
+            self.ensure_schedule_amount(amount)?;
+
+            for (&id, schedule) in ids.iter().zip(&schedules) {
+                self.remove_schedule(id);
+                self.remove_beneficiary_id(schedule.beneficiary, id);
+                self.remove_owner_id(caller, id);
+                self.unlock_funds(schedule.beneficiary, schedule.amount);
+            }
+
+            let new_id = self.insert_schedule(VestingSchedule {
+                amount,
+                beneficiary_locked: schedules.iter().any(|schedule| schedule.beneficiary_locked),
+                ..first
+            })?;
+
+            self.env().emit_event(Merged {
+                id: new_id,
+                merged_ids: ids,
+                amount,
+            });
+
+            Ok(new_id)
+        }
+
+        /// Withdraw all available vested funds for the caller.
+        ///
+        /// If the transfer fails, the funds are credited to the caller and can be
+        /// pulled later with `claim_credit`. The protocol fee, if any, is deducted
+        /// from the payout.
+        ///
+        /// Returns the amount paid to the caller. Every schedule, index and total is updated
+        /// before the funds are transferred, so a re-entrant call finds nothing left to withdraw.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::NoSchedules` if the caller has no vesting schedules.
+        /// Returns `Error::CooldownActive` if the last withdrawal is more recent than the cooldown.
+        /// Returns `Error::NoFundsAvailable` if no funds are available for withdrawal.
+        /// Returns `Error::Paused` if the contract is paused.
+        /// Returns `Error::Reentrancy` if called while a withdrawal is in progress.
+        /// Returns `Error::AmountOverflow` if the released amounts overflow when added up.
+        /// Returns `Error::OutflowCapReached` if the withdrawal would exceed the outflow cap.
+        #[ink(message)]
+        pub fn withdraw_fund(&mut self) -> Result<Balance> {
+            let beneficiary = self.env().caller();
+            self.withdraw_all(beneficiary, Payout::Transfer(beneficiary))
+        }
+
+        /// Withdraw all available vested funds of the caller to another account.
+        ///
+        /// The caller stays the beneficiary of its schedules, only the payout goes to
+        /// `recipient`. If the transfer fails, the funds are credited to `recipient`.
+        ///
+        /// # Arguments
+        ///
+        /// * `recipient`: The account receiving the funds.
+        ///
+        /// Returns the amount withdrawn.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::NoSchedules` if the caller has no vesting schedules.
+        /// Returns `Error::CooldownActive` if the last withdrawal is more recent than the cooldown.
+        /// Returns `Error::NoFundsAvailable` if no funds are available for withdrawal.
+        /// Returns `Error::Paused` if the contract is paused.
+        /// Returns `Error::Reentrancy` if called while a withdrawal is in progress.
+        /// Returns `Error::AmountOverflow` if the released amounts overflow when added up.
+        /// Returns `Error::OutflowCapReached` if the withdrawal would exceed the outflow cap.
+        #[ink(message)]
+        pub fn withdraw_to(&mut self, recipient: AccountId) -> Result<Balance> {
+            let beneficiary = self.env().caller();
+            self.withdraw_all(beneficiary, Payout::Transfer(recipient))
+        }
+
+        /// Withdraw all available vested funds of the caller and stake them on a staking
+        /// contract instead of transferring them.
+        ///
+        /// The funds, after the protocol fee, are attached to a `stake(on_behalf_of)` call
+        /// with the caller as `on_behalf_of`. If the call fails the whole withdrawal fails,
+        /// so the funds stay in their schedules. Only available when vesting the native balance.
+        ///
+        /// # Arguments
+        ///
+        /// * `staking_contract`: The contract staking the funds.
+        ///
+        /// Returns the amount staked.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::WrongMode` if the contract vests a PSP22 token.
+        /// Returns `Error::StakeFailed` if the staking call fails.
+        /// Returns `Error::NoSchedules` if the caller has no vesting schedules.
+        /// Returns `Error::CooldownActive` if the last withdrawal is more recent than the cooldown.
+        /// Returns `Error::NoFundsAvailable` if no funds are available for withdrawal.
+        /// Returns `Error::Paused` if the contract is paused.
+        /// Returns `Error::Reentrancy` if called while a withdrawal is in progress.
+        /// Returns `Error::AmountOverflow` if the released amounts overflow when added up.
+        /// Returns `Error::OutflowCapReached` if the withdrawal would exceed the outflow cap.
+        #[ink(message)]
+        pub fn withdraw_and_stake(&mut self, staking_contract: AccountId) -> Result<Balance> {
+            // Tokens cannot be attached to the staking call
+            if self.mode != VestingMode::Native {
+                return Err(Error::WrongMode);
+            }
+
+            let beneficiary = self.env().caller();
+            self.withdraw_all(beneficiary, Payout::Stake(staking_contract))
+        }
+
+        /// Withdraw all available vested funds of `beneficiary` on their behalf, e.g. when
+        /// they have no funds to pay for the transaction.
+        ///
+        /// Anyone can relay a withdrawal once `beneficiary` allowed it with
+        /// `set_relayer_tip_cap`. The caller receives a tip of `relayer_tip_bps` of the
+        /// payout, after the protocol fee, and `beneficiary` receives the rest.
+        ///
+        /// # Arguments
+        ///
+        /// * `beneficiary`: The account whose funds are withdrawn.
+        /// * `relayer_tip_bps`: The tip in basis points of the payout, at most 100 (1%).
+        ///
+        /// Returns the amount paid to `beneficiary`.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::RelayNotAllowed` if `beneficiary` did not allow relayed withdrawals.
+        /// Returns `Error::TipTooHigh` if `relayer_tip_bps` is above the cap set by `beneficiary`.
+        /// Returns `Error::NoSchedules` if `beneficiary` has no vesting schedules.
+        /// Returns `Error::CooldownActive` if the last withdrawal is more recent than the cooldown.
+        /// Returns `Error::NoFundsAvailable` if no funds are available for withdrawal.
+        /// Returns `Error::Paused` if the contract is paused.
+        /// Returns `Error::Reentrancy` if called while a withdrawal is in progress.
+        /// Returns `Error::AmountOverflow` if the released amounts overflow when added up.
+        /// Returns `Error::OutflowCapReached` if the withdrawal would exceed the outflow cap.
+        #[ink(message)]
+        pub fn withdraw_fund_relayed(
+            &mut self,
+            beneficiary: AccountId,
+            relayer_tip_bps: u16
+        ) -> Result<Balance> {
+            // The beneficiary decides whether and for how much its claims can be relayed
+            let tip_cap = self.relayer_tip_caps.get(beneficiary).ok_or(Error::RelayNotAllowed)?;
+            if relayer_tip_bps > tip_cap {
+                return Err(Error::TipTooHigh);
+            }
+
+            let relayer = self.env().caller();
+            self.withdraw_all(beneficiary, Payout::Relay { relayer, tip_bps: relayer_tip_bps })
+        }
+
+        /// Withdraw part of the available vested funds of the caller, taking it from
+        /// the oldest schedules first. The rest stays claimable.
+        ///
+        /// # Arguments
+        ///
+        /// * `amount`: The amount to release, before the protocol fee.
+        ///
+        /// Returns the amount paid to the caller.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::ZeroAmount` if `amount` is zero.
+        /// Returns `Error::ExceedsAvailable` if `amount` is more than the funds available.
+        /// Returns `Error::NoSchedules` if the caller has no vesting schedules.
+        /// Returns `Error::CooldownActive` if the last withdrawal is more recent than the cooldown.
+        /// Returns `Error::NoFundsAvailable` if no funds are available for withdrawal.
+        /// Returns `Error::Paused` if the contract is paused.
+        /// Returns `Error::Reentrancy` if called while a withdrawal is in progress.
+        /// Returns `Error::AmountOverflow` if the released amounts overflow when added up.
+        /// Returns `Error::OutflowCapReached` if the withdrawal would exceed the outflow cap.
+        #[ink(message)]
+        pub fn withdraw_partial(&mut self, amount: Balance) -> Result<Balance> {
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+
+            let beneficiary = self.env().caller();
+            self.withdraw_up_to(beneficiary, Payout::Transfer(beneficiary), Some(amount))
+        }
+
+        /// Allow anyone to relay withdrawals of the caller's funds with
+        /// `withdraw_fund_relayed`, for a tip of at most `max_tip_bps`.
+        ///
+        /// # Arguments
+        ///
+        /// * `max_tip_bps`: The highest tip in basis points of the payout, at most 100 (1%),
+        ///   `None` to stop relayed withdrawals.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::TipTooHigh` if `max_tip_bps` is above 100.
+        #[ink(message)]
+        pub fn set_relayer_tip_cap(&mut self, max_tip_bps: Option<u16>) -> Result<()> {
+            let beneficiary = self.env().caller();
+            match max_tip_bps {
+                Some(max_tip_bps) if max_tip_bps > MAX_RELAYER_TIP_BPS => Err(Error::TipTooHigh),
+                Some(max_tip_bps) => {
+                    self.relayer_tip_caps.insert(beneficiary, &max_tip_bps);
+                    Ok(())
+                }
+                None => {
+                    self.relayer_tip_caps.remove(beneficiary);
+                    Ok(())
+                }
+            }
+        }
+
+        /// Register the account allowed to trigger withdrawals on behalf of the caller.
+        ///
+        /// The delegate only triggers the withdrawal, the funds are still sent to the caller.
+        /// Setting a new delegate replaces the previous one.
+        ///
+        /// # Arguments
+        ///
+        /// * `delegate`: The account allowed to call `withdraw_for` for the caller.
+        #[ink(message)]
+        pub fn set_withdraw_delegate(&mut self, delegate: AccountId) {
+            let beneficiary = self.env().caller();
+            self.withdraw_delegates.insert(beneficiary, &delegate);
+        }
+
+        /// Choose whether the caller receives its payouts through an
+        /// `on_vest_received(ids, amount)` call carrying the funds, so a beneficiary that
+        /// is a contract can react to them. `ids` are the schedules the funds come from.
+        ///
+        /// If the callback fails, the funds are credited to the caller instead and can be
+        /// pulled later with `claim_credit`.
+        ///
+        /// # Arguments
+        ///
+        /// * `enabled`: Whether payouts to the caller go through the callback.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::WrongMode` if the contract vests a PSP22 token.
+        #[ink(message)]
+        pub fn set_vest_callback(&mut self, enabled: bool) -> Result<()> {
+            // Tokens cannot be attached to the callback
+            if self.mode != VestingMode::Native {
+                return Err(Error::WrongMode);
+            }
+
+            let caller = self.env().caller();
+            if enabled {
+                self.vest_callbacks.insert(caller, &true);
+            } else {
+                self.vest_callbacks.remove(caller);
+            }
+            Ok(())
+        }
+
+        /// Withdraw all available vested funds of `beneficiary` to `beneficiary`.
+        ///
+        /// # Arguments
+        ///
+        /// * `beneficiary`: The account whose funds are withdrawn.
+        ///
+        /// Returns the amount withdrawn.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::NotAuthorized` if the caller is not the delegate of `beneficiary`.
+        /// Returns `Error::NoSchedules` if `beneficiary` has no vesting schedules.
+        /// Returns `Error::CooldownActive` if the last withdrawal is more recent than the cooldown.
+        /// Returns `Error::NoFundsAvailable` if no funds are available for withdrawal.
+        /// Returns `Error::Paused` if the contract is paused.
+        /// Returns `Error::Reentrancy` if called while a withdrawal is in progress.
+        /// Returns `Error::AmountOverflow` if the released amounts overflow when added up.
+        /// Returns `Error::OutflowCapReached` if the withdrawal would exceed the outflow cap.
+        #[ink(message)]
+        pub fn withdraw_for(&mut self, beneficiary: AccountId) -> Result<Balance> {
+            let caller = self.env().caller();
+            if self.withdraw_delegates.get(beneficiary) != Some(caller) {
+                return Err(Error::NotAuthorized);
+            }
+            self.withdraw_all(beneficiary, Payout::Transfer(beneficiary))
+        }
+
+        /// Withdraw the available vested funds of each of `beneficiaries` to themselves,
+        /// e.g. to run payroll in a single transaction.
+        ///
+        /// Beneficiaries without schedules, without available funds or still in their
+        /// cooldown are skipped instead of failing the whole batch.
+        ///
+        /// # Arguments
+        ///
+        /// * `beneficiaries`: The accounts whose funds are withdrawn.
+        ///
+        /// Returns the amount paid to each beneficiary that was not skipped.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::NotAdmin` if the caller is not the admin.
+        /// Returns `Error::Paused` if the contract is paused.
+        /// Returns `Error::Reentrancy` if called while a withdrawal is in progress.
+        /// Returns `Error::AmountOverflow` if the released amounts overflow when added up.
+        /// Returns `Error::OutflowCapReached` if the withdrawal would exceed the outflow cap.
+        #[ink(message)]
+        pub fn distribute_matured(
+            &mut self,
+            beneficiaries: Vec<AccountId>
+        ) -> Result<Vec<(AccountId, Balance)>> {
+            self.ensure_admin()?;
+            self.ensure_not_paused()?;
+
+            let mut paid = Vec::new();
+            for beneficiary in beneficiaries {
+                match self.withdraw_all(beneficiary, Payout::Transfer(beneficiary)) {
+                    Ok(amount) => paid.push((beneficiary, amount)),
+                    Err(Error::NoSchedules | Error::NoFundsAvailable | Error::CooldownActive) => {}
+                    Err(error) => return Err(error),
+                }
+            }
+
+            Ok(paid)
+        }
+
+        /// Withdraw the available vested funds of at most `max` schedules of the caller.
+        ///
+        /// Schedules are processed from the front of the caller's list. Processed
+        /// schedules that still hold locked funds are moved to the back, so repeated
+        /// calls page through the whole list and eventually drain all matured funds.
+        ///
+        /// # Arguments
+        ///
+        /// * `max`: The maximum number of schedules to process.
+        ///
+        /// Returns the amount released, which is zero when none of the processed
+        /// schedules had funds available. If the transfer fails, the funds are
+        /// credited to the caller and can be pulled later with `claim_credit`.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::Paused` if the contract is paused.
+        /// Returns `Error::Reentrancy` if called while a withdrawal is in progress.
+        /// Returns `Error::AmountOverflow` if the released amounts overflow when added up.
+        /// Returns `Error::OutflowCapReached` if the withdrawal would exceed the outflow cap.
+        /// Returns `Error::CooldownActive` if the last withdrawal is more recent than the cooldown.
+        #[ink(message)]
+        pub fn withdraw_fund_limited(&mut self, max: u32) -> Result<Balance> {
+            let beneficiary = self.env().caller();
+            self.ensure_cooldown_elapsed(beneficiary, self.env().block_timestamp())?;
+            let paid = self.non_reentrant(|this| {
+                this.withdraw_matured(beneficiary, Payout::Transfer(beneficiary), max as usize, Balance::MAX)
+            })?;
+            if paid > 0 {
+                self.notify_withdrawal(beneficiary, paid);
+            }
+            Ok(paid)
+        }
+
+        /// Withdraw the available vested funds of a single schedule.
+        ///
+        /// If the transfer fails, the funds are credited to the caller and can be
+        /// pulled later with `claim_credit`.
+        ///
+        /// # Arguments
+        ///
+        /// * `id`: The ID of the schedule to withdraw from.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::ScheduleNotFound` if no schedule exists for `id`.
+        /// Returns `Error::NotBeneficiary` if the caller is not the beneficiary of the schedule.
+        /// Returns `Error::NotAccepted` if the caller has not accepted the schedule yet.
+        /// Returns `Error::NotYetUnlocked` if no funds of the schedule are available yet.
+        /// Returns `Error::Paused` if the contract is paused.
+        /// Returns `Error::Reentrancy` if called while a withdrawal is in progress.
+        /// Returns `Error::OutflowCapReached` if the withdrawal would exceed the outflow cap.
+        /// Returns `Error::CooldownActive` if the last withdrawal is more recent than the cooldown.
+        #[ink(message)]
+        pub fn withdraw_schedule(&mut self, id: u64) -> Result<Balance> {
+            let beneficiary = self.env().caller();
+            let paid = self.non_reentrant(|this| {
+                this.ensure_not_paused()?;
+                let current_time: Timestamp = this.env().block_timestamp();
+                let current_block = this.env().block_number();
+
+                let mut schedule = this.load_schedule(id).ok_or(Error::ScheduleNotFound)?;
+
+                // Only the beneficiary can claim the funds, once they accepted them
+                if beneficiary != schedule.beneficiary {
+                    return Err(Error::NotBeneficiary);
+                }
+                if schedule.pending_acceptance {
+                    return Err(Error::NotAccepted);
+                }
+                this.ensure_cooldown_elapsed(beneficiary, current_time)?;
+
+                this.record_outflow(schedule.claimable(current_time, current_block), current_time)?;
+                let amount = this.release(id, &mut schedule, current_time, current_block, Balance::MAX);
+                if amount == 0 {
+                    return Err(Error::NotYetUnlocked);
+                }
+                this.start_cooldown(beneficiary, current_time);
+
+                // Drop the schedule from the beneficiary's list once fully released
+                if schedule.released == schedule.amount {
+                    this.remove_beneficiary_id(beneficiary, id);
+                }
+
+                let paid = this.net_payout(Payout::Transfer(beneficiary), amount);
+                this.record_claim(beneficiary, current_time, paid);
+
+                this.env().emit_event(Withdrawn {
+                    beneficiary,
+                    total_amount: amount,
+                    schedule_ids: vec![id],
+                });
+
+                // Funds leave the contract only once every record is updated
+                this.pay_out(beneficiary, amount, &[id]);
+                Ok(paid)
+            })?;
+
+            self.notify_withdrawal(beneficiary, paid);
+            Ok(paid)
+        }
+
+        /// Claim the funds credited to the caller after a failed transfer.
+        ///
+        /// Returns the amount transferred.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::NoFundsAvailable` if the caller has no credited funds.
+        /// Returns `Error::TransferFailed` if the token transfer fails.
+        /// Returns `Error::Paused` if the contract is paused.
+        /// Returns `Error::Reentrancy` if called while a withdrawal is in progress.
+        #[ink(message)]
+        pub fn claim_credit(&mut self) -> Result<Balance> {
+            self.non_reentrant(|this| {
+                this.ensure_not_paused()?;
+                let caller = this.env().caller();
+                let amount = this.credited_balances.get(caller).unwrap_or_default();
+                if amount == 0 {
+                    return Err(Error::NoFundsAvailable);
+                }
+
+                // Clear the credit before transferring it
+                this.credited_balances.remove(caller);
+                this.total_credited = this.total_credited.saturating_sub(amount);
+                this.transfer_out(caller, amount)?;
+
+                Ok(amount)
+            })
+        }
+
+        /// Renounce a schedule as its beneficiary, returning the funds it still holds
+        /// to the owner.
+        ///
+        /// # Arguments
+        ///
+        /// * `id`: The ID of the schedule to renounce.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::ScheduleNotFound` if no schedule exists for `id`.
+        /// Returns `Error::NotBeneficiary` if the caller is not the beneficiary of the schedule.
+        /// Returns `Error::TransferFailed` if the transfer to the owner fails.
+        /// Returns `Error::Paused` if the contract is paused.
+        #[ink(message)]
+        pub fn renounce(&mut self, id: u64) -> Result<()> {
+            self.ensure_not_paused()?;
+            let caller = self.env().caller();
+
+            let schedule = self.load_schedule(id).ok_or(Error::ScheduleNotFound)?;
+
+            // Only the beneficiary can give up the funds
+            if caller != schedule.beneficiary {
+                return Err(Error::NotBeneficiary);
+            }
+
+            // Remove the schedule before returning the funds
+            let amount = schedule.amount - schedule.released;
+            self.remove_schedule(id);
+            self.remove_beneficiary_id(schedule.beneficiary, id);
+            self.remove_owner_id(schedule.owner, id);
+            self.unlock_funds(schedule.beneficiary, amount);
+
+            self.transfer_out(schedule.owner, amount)?;
+
+            self.env().emit_event(Renounced {
+                id,
+                beneficiary: caller,
+                amount,
+            });
+
+            Ok(())
+        }
+
+        /// Revoke a schedule that has not started vesting and refund its owner.
+        ///
+        /// # Arguments
+        ///
+        /// * `id`: The ID of the schedule to revoke.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::ScheduleNotFound` if no schedule exists for `id`.
+        /// Returns `Error::NotOwner` if the caller did not create the schedule.
+        /// Returns `Error::NotRevocable` if the schedule was created as irrevocable.
+        /// Returns `Error::AlreadyVested` if any part of the schedule has already vested and the
+        /// beneficiary accepted it.
+        /// Returns `Error::RevokeNotApproved` if the schedule is large and the co-signer has not
+        /// approved its revocation.
+        /// Returns `Error::TransferFailed` if the refund transfer fails.
+        /// Returns `Error::Paused` if the contract is paused.
+        #[ink(message)]
+        pub fn revoke(&mut self, id: u64) -> Result<()> {
+            self.ensure_not_paused()?;
+            let caller = self.env().caller();
+            let current_time: Timestamp = self.env().block_timestamp();
+            let current_block = self.env().block_number();
+
+            let schedule = self.load_schedule(id).ok_or(Error::ScheduleNotFound)?;
+
+            // Only the creator of the schedule can reclaim the funds
+            if caller != schedule.owner {
+                return Err(Error::NotOwner);
+            }
+
+            // The owner committed not to take the funds back
+            if !schedule.revocable {
+                return Err(Error::NotRevocable);
+            }
+
+            // Vested funds belong to the beneficiary, once they accepted the schedule
+            if schedule.has_vested(current_time, current_block) {
+                return Err(Error::AlreadyVested);
+            }
+
+            self.ensure_revoke_approved(id, schedule.amount)?;
+
+            // Remove the schedule before refunding the owner
+            self.remove_schedule(id);
+            self.remove_beneficiary_id(schedule.beneficiary, id);
+            self.remove_owner_id(schedule.owner, id);
+            self.unlock_funds(schedule.beneficiary, schedule.amount);
+
+            self.transfer_out(schedule.owner, schedule.amount)?;
+
+            self.env().emit_event(Revoked {
+                id,
+                owner: schedule.owner,
+                amount: schedule.amount,
+            });
+
+            Ok(())
+        }
+
+        /// Revoke every schedule the caller created for a beneficiary that has not started
+        /// vesting, and refund them in a single transfer.
+        ///
+        /// Irrevocable schedules, schedules that have started vesting and large schedules
+        /// the co-signer has not approved are kept.
+        ///
+        /// # Arguments
+        ///
+        /// * `beneficiary`: The account whose schedules are revoked.
+        ///
+        /// Returns the total amount refunded to the caller.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::AmountOverflow` if the refunded amounts overflow when added up.
+        /// Returns `Error::TransferFailed` if the refund transfer fails.
+        /// Returns `Error::Paused` if the contract is paused.
+        #[ink(message)]
+        pub fn revoke_all_for(&mut self, beneficiary: AccountId) -> Result<Balance> {
+            self.ensure_not_paused()?;
+            let caller = self.env().caller();
+            let current_time: Timestamp = self.env().block_timestamp();
+            let current_block = self.env().block_number();
+
+            let ids = self.beneficiary_to_ids.get(beneficiary).unwrap_or_default();
+            let mut kept_ids = Vec::new();
+            let mut total_amount: Balance = 0;
+
+            for id in ids {
+                let Some(schedule) = self.load_schedule(id) else {
+                    continue;
+                };
+
+                // Same conditions as `revoke`, other schedules are silently kept
+                let vested = schedule.has_vested(current_time, current_block);
+                let approved = self.ensure_revoke_approved(id, schedule.amount).is_ok();
+                if schedule.owner != caller || !schedule.revocable || vested || !approved {
+                    kept_ids.push(id);
+                    continue;
+                }
+
+                total_amount = total_amount.checked_add(schedule.amount).ok_or(Error::AmountOverflow)?;
+                self.remove_schedule(id);
+                self.remove_owner_id(caller, id);
+                self.unlock_funds(beneficiary, schedule.amount);
+
+                self.env().emit_event(Revoked {
+                    id,
+                    owner: caller,
+                    amount: schedule.amount,
+                });
+            }
+            self.store_beneficiary_ids(beneficiary, &kept_ids);
+
+            // Refund the owner once all schedules are removed
+            if total_amount > 0 {
+                self.transfer_out(caller, total_amount)?;
+            }
+
+            Ok(total_amount)
+        }
+
+        /// Revoke the unvested part of a schedule and refund it to its owner.
+        ///
+        /// The portion vested so far stays with the beneficiary: the schedule is reduced
+        /// to that portion and unlocked immediately.
+        ///
+        /// # Arguments
+        ///
+        /// * `id`: The ID of the schedule to revoke.
+        ///
+        /// Returns the amount refunded to the owner.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::ScheduleNotFound` if no schedule exists for `id`.
+        /// Returns `Error::NotOwner` if the caller did not create the schedule.
+        /// Returns `Error::NotRevocable` if the schedule was created as irrevocable.
+        /// Returns `Error::AlreadyVested` if the schedule has fully vested.
+        /// Returns `Error::RevokeNotApproved` if the refund is large and the co-signer has not
+        /// approved the revocation.
+        /// Returns `Error::TransferFailed` if the refund transfer fails.
+        /// Returns `Error::Paused` if the contract is paused.
+        #[ink(message)]
+        pub fn revoke_partial(&mut self, id: u64) -> Result<Balance> {
+            self.ensure_not_paused()?;
+            let caller = self.env().caller();
+            let current_time: Timestamp = self.env().block_timestamp();
+            let current_block = self.env().block_number();
+
+            let mut schedule = self.load_schedule(id).ok_or(Error::ScheduleNotFound)?;
+
+            // Only the creator of the schedule can reclaim the funds
+            if caller != schedule.owner {
+                return Err(Error::NotOwner);
+            }
+
+            // The owner committed not to take the funds back
+            if !schedule.revocable {
+                return Err(Error::NotRevocable);
+            }
+
+            // Only the unvested part can be reclaimed, nothing has vested until accepted
+            let vested = if schedule.pending_acceptance {
+                0
+            } else {
+                schedule.vested_amount(current_time, current_block)
+            };
+            let refund = schedule.amount - vested;
+            if refund == 0 {
+                return Err(Error::AlreadyVested);
+            }
+
+            self.ensure_revoke_approved(id, refund)?;
+            self.revoke_approvals.remove(id);
+
+            if vested == schedule.released {
+                // Nothing left for the beneficiary, remove the schedule
+                self.remove_schedule(id);
+                self.remove_beneficiary_id(schedule.beneficiary, id);
+                self.remove_owner_id(schedule.owner, id);
+            } else {
+                // Keep the vested portion claimable right away
+                schedule.amount = vested;
+                schedule.kind = VestingKind::Cliff { unlock_time: current_time };
+                self.store_schedule(id, &schedule);
+            }
+            self.unlock_funds(schedule.beneficiary, refund);
+
+            self.transfer_out(schedule.owner, refund)?;
+
+            self.env().emit_event(Revoked {
+                id,
+                owner: schedule.owner,
+                amount: refund,
+            });
+
+            Ok(refund)
+        }
+
+        /// Returns the number of schedules of a beneficiary.
+        ///
+        /// # Arguments
+        ///
+        /// * `beneficiary`: The account whose schedules are counted.
+        #[ink(message)]
+        pub fn schedule_count(&self, beneficiary: AccountId) -> u32 {
+            let count = self.beneficiary_to_ids.get(beneficiary).unwrap_or_default().len();
+            u32::try_from(count).unwrap_or(u32::MAX)
+        }
+
+        /// Returns a page of the schedule IDs of a beneficiary.
+        ///
+        /// # Arguments
+        ///
+        /// * `beneficiary`: The account whose schedule IDs are returned.
+        /// * `start`: The position of the first ID to return.
+        /// * `len`: The maximum number of IDs to return.
+        #[ink(message)]
+        pub fn get_schedule_ids_paged(&self, beneficiary: AccountId, start: u32, len: u32) -> Vec<u64> {
+            self.beneficiary_to_ids
+                .get(beneficiary)
+                .unwrap_or_default()
+                .into_iter()
+                .skip(start as usize)
+                .take(len as usize)
+                .collect()
+        }
+
+        /// Redirect a schedule that has not started vesting to a new beneficiary.
+        ///
+        /// # Arguments
+        ///
+        /// * `id`: The ID of the schedule to reassign.
+        /// * `new_beneficiary`: The account that will receive the vested funds instead.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::ScheduleNotFound` if no schedule exists for `id`.
+        /// Returns `Error::NotOwner` if the caller did not create the schedule.
+        /// Returns `Error::BeneficiaryLocked` if the beneficiary locked the schedule.
+        /// Returns `Error::AlreadyVested` if any part of the schedule has already vested.
+        /// Returns `Error::InvalidBeneficiary` if `new_beneficiary` is the zero address or the
+        /// contract itself.
+        /// Returns `Error::TooManySchedules` if `new_beneficiary` already has the maximum number of
+        /// schedules.
+        /// Returns `Error::AmountOverflow` if the locked totals overflow.
+        #[ink(message)]
+        pub fn reassign_beneficiary(&mut self, id: u64, new_beneficiary: AccountId) -> Result<()> {
+            let caller = self.env().caller();
+            let current_time: Timestamp = self.env().block_timestamp();
+            let current_block = self.env().block_number();
+
+            let mut schedule = self.load_schedule(id).ok_or(Error::ScheduleNotFound)?;
+
+            // Only the creator of the schedule can redirect it
+            if caller != schedule.owner {
+                return Err(Error::NotOwner);
+            }
+
+            // The beneficiary opted out of any reassignment
+            if schedule.beneficiary_locked {
+                return Err(Error::BeneficiaryLocked);
+            }
+
+            // Vested funds cannot be taken away from the current beneficiary
+            if schedule.vested_amount(current_time, current_block) > 0 {
+                return Err(Error::AlreadyVested);
+            }
+
+            self.ensure_valid_beneficiary(new_beneficiary)?;
+            self.ensure_schedule_capacity(new_beneficiary)?;
+
+            // Move the schedule and its funds between the beneficiaries
+            let old_beneficiary = schedule.beneficiary;
+            self.lock_funds(new_beneficiary, schedule.amount)?;
+            self.unlock_funds(old_beneficiary, schedule.amount);
+            self.remove_beneficiary_id(old_beneficiary, id);
+            self.add_beneficiary_id(new_beneficiary, id);
+
+            schedule.beneficiary = new_beneficiary;
+            self.store_schedule(id, &schedule);
+
+            self.env().emit_event(BeneficiaryReassigned {
+                id,
+                old_beneficiary,
+                new_beneficiary,
+            });
+
+            Ok(())
+        }
+
+        /// Lock the beneficiary of a schedule, so that its owner can never reassign it.
+        ///
+        /// # Arguments
+        ///
+        /// * `id`: The ID of the schedule to lock.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::ScheduleNotFound` if no schedule exists for `id`.
+        /// Returns `Error::NotBeneficiary` if the caller is not the beneficiary of the schedule.
+        #[ink(message)]
+        pub fn lock_beneficiary(&mut self, id: u64) -> Result<()> {
+            let caller = self.env().caller();
+
+            let mut schedule = self.load_schedule(id).ok_or(Error::ScheduleNotFound)?;
+
+            // Only the beneficiary can accept the grant as final
+            if caller != schedule.beneficiary {
+                return Err(Error::NotBeneficiary);
+            }
+
+            schedule.beneficiary_locked = true;
+            self.store_schedule(id, &schedule);
+
+            Ok(())
+        }
+
+        /// Accept a schedule created pending the beneficiary's acceptance, so that its
+        /// vested funds can be withdrawn. Accepting a schedule twice has no effect.
+        ///
+        /// # Arguments
+        ///
+        /// * `id`: The ID of the schedule to accept.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::ScheduleNotFound` if no schedule exists for `id`.
+        /// Returns `Error::NotBeneficiary` if the caller is not the beneficiary of the schedule.
+        #[ink(message)]
+        pub fn accept_schedule(&mut self, id: u64) -> Result<()> {
+            let caller = self.env().caller();
+
+            let mut schedule = self.load_schedule(id).ok_or(Error::ScheduleNotFound)?;
+
+            // Only the beneficiary can accept the grant
+            if caller != schedule.beneficiary {
+                return Err(Error::NotBeneficiary);
+            }
+            if !schedule.pending_acceptance {
+                return Ok(());
+            }
+
+            schedule.pending_acceptance = false;
+            self.store_schedule(id, &schedule);
+
+            self.env().emit_event(ScheduleAccepted {
+                id,
+                beneficiary: caller,
+            });
+
+            Ok(())
+        }
+
+        /// Returns the funds held by all schedules that have not been withdrawn yet.
+        #[ink(message)]
+        pub fn total_locked(&self) -> Balance {
+            self.total_locked
+        }
+
+        /// Returns the funds held by the schedules of a beneficiary that have not
+        /// been withdrawn yet.
+        ///
+        /// # Arguments
+        ///
+        /// * `beneficiary`: The account whose locked funds are returned.
+        #[ink(message)]
+        pub fn locked_of(&self, beneficiary: AccountId) -> Balance {
+            self.locked_by_beneficiary.get(beneficiary).unwrap_or_default()
+        }
+
+        /// Returns contract-wide statistics for monitoring.
+        #[ink(message)]
+        pub fn stats(&self) -> ContractStats {
+            ContractStats {
+                total_schedules: self.schedule_count,
+                next_id: self.id,
+                total_locked: self.total_locked,
+                total_withdrawn: self.total_withdrawn,
+            }
+        }
+
+        /// Returns the `(actual, tracked)` balance of the vested asset held by the
+        /// contract, where `tracked` is the sum of the locked and credited funds.
+        ///
+        /// `actual` is the native balance, or the contract's token balance if it vests
+        /// a PSP22 token, reported as 0 if the token cannot be queried. A mismatch points
+        /// to untracked funds sent to the contract or to an accounting bug.
+        #[ink(message)]
+        pub fn balance_report(&self) -> (Balance, Balance) {
+            let actual = match self.mode {
+                VestingMode::Token(token) => self.token_balance(token).unwrap_or_default(),
+                VestingMode::Native => self.env().balance(),
+            };
+            (actual, self.total_locked.saturating_add(self.total_credited))
+        }
+
+        /// Returns the `(claimable, locked)` split of a beneficiary's funds at the
+        /// current block time. `claimable` can be withdrawn right now, `locked` has
+        /// not vested yet.
+        ///
+        /// # Arguments
+        ///
+        /// * `beneficiary`: The account whose funds are reported.
+        #[ink(message)]
+        pub fn balances_of(&self, beneficiary: AccountId) -> (Balance, Balance) {
+            let current_time: Timestamp = self.env().block_timestamp();
+            let current_block = self.env().block_number();
+            self.beneficiary_to_ids
+                .get(beneficiary)
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|id| self.load_schedule(id))
+                .fold((0, 0), |(claimable, locked), schedule| {
+                    let vested = schedule.vested_amount(current_time, current_block);
+                    (
+                        claimable.saturating_add(schedule.claimable(current_time, current_block)),
+                        locked.saturating_add(schedule.amount - vested),
+                    )
+                })
+        }
+
+        /// Returns a single schedule, or `None` if it does not exist or was fully withdrawn.
+        ///
+        /// # Arguments
+        ///
+        /// * `id`: The ID of the schedule.
+        #[ink(message)]
+        pub fn get_schedule(&self, id: u64) -> Option<VestingScheduleInfo> {
+            let current_time: Timestamp = self.env().block_timestamp();
+            let current_block = self.env().block_number();
+            self.load_schedule(id).map(|schedule| schedule.info(current_time, current_block))
+        }
+
+        /// Returns the amount of a schedule that can be withdrawn right now, or 0 if
+        /// the schedule does not exist.
+        ///
+        /// # Arguments
+        ///
+        /// * `id`: The ID of the schedule.
+        #[ink(message)]
+        pub fn claimable_amount(&self, id: u64) -> Balance {
+            let current_time: Timestamp = self.env().block_timestamp();
+            let current_block = self.env().block_number();
+            self.load_schedule(id)
+                .map(|schedule| schedule.claimable(current_time, current_block))
+                .unwrap_or_default()
+        }
+
+        /// Returns the share of a schedule vested so far in basis points, from 0 to 10000,
+        /// or 0 if the schedule does not exist.
+        ///
+        /// # Arguments
+        ///
+        /// * `id`: The ID of the schedule.
+        #[ink(message)]
+        pub fn vesting_progress(&self, id: u64) -> u16 {
+            let current_time: Timestamp = self.env().block_timestamp();
+            let current_block = self.env().block_number();
+            let Some(schedule) = self.load_schedule(id) else {
+                return 0;
+            };
+            if schedule.amount == 0 {
+                return 0;
+            }
+
+            let vested = schedule.vested_amount(current_time, current_block);
+            // vested * 10000 / amount, falling back to a coarser division for huge amounts
+            let progress = match vested.checked_mul(10_000) {
+                Some(scaled) => scaled / schedule.amount,
+                None => vested / (schedule.amount / 10_000),
+            };
+            u16::try_from(progress.min(10_000)).unwrap_or(10_000)
+        }
+
+        /// Returns whether the schedule exists and its full amount is unlocked.
+        ///
+        /// # Arguments
+        ///
+        /// * `id`: The ID of the schedule.
+        #[ink(message)]
+        pub fn is_unlocked(&self, id: u64) -> bool {
+            let current_time: Timestamp = self.env().block_timestamp();
+            let current_block = self.env().block_number();
+            self.load_schedule(id).is_some_and(|schedule| {
+                schedule.vested_amount(current_time, current_block) == schedule.amount
+            })
+        }
+
+        /// Returns all schedules of a beneficiary, paired with their IDs.
+        ///
+        /// # Arguments
+        ///
+        /// * `beneficiary`: The account whose schedules are returned.
+        #[ink(message)]
+        pub fn get_schedules(&self, beneficiary: AccountId) -> Vec<(u64, VestingScheduleInfo)> {
+            let current_time: Timestamp = self.env().block_timestamp();
+            let current_block = self.env().block_number();
+            self.beneficiary_to_ids
+                .get(beneficiary)
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|id| {
+                    self.load_schedule(id).map(|schedule| (id, schedule.info(current_time, current_block)))
+                })
+                .collect()
+        }
+
+        /// Returns a page of all active schedules, paired with their IDs, for auditing.
+        ///
+        /// Schedules are returned in no particular order: removing a schedule moves the
+        /// last one into its position, so pages may shift between calls.
+        ///
+        /// # Arguments
+        ///
+        /// * `start`: The position of the first schedule to return.
+        /// * `len`: The maximum number of schedules to return.
+        #[ink(message)]
+        pub fn export_schedules(&self, start: u32, len: u32) -> Vec<(u64, VestingScheduleInfo)> {
+            let current_time: Timestamp = self.env().block_timestamp();
+            let current_block = self.env().block_number();
+            let start = u64::from(start);
+            let end = start.saturating_add(u64::from(len)).min(self.schedule_count);
+            (start..end)
+                .filter_map(|position| self.active_ids.get(position))
+                .filter_map(|id| {
+                    self.load_schedule(id).map(|schedule| (id, schedule.info(current_time, current_block)))
+                })
+                .collect()
+        }
+
+        /// Returns the active schedules that become fully vested between `start` and `end`,
+        /// both included, as their ID, beneficiary and amount not yet released.
+        ///
+        /// Like `export_schedules`, the scan follows the active schedules in no particular
+        /// order. Pass a `page` to bound the scan on contracts with many schedules.
+        ///
+        /// # Arguments
+        ///
+        /// * `start`: The earliest unlock time to include.
+        /// * `end`: The latest unlock time to include.
+        /// * `page`: The position of the first schedule to scan and the maximum number of
+        ///   schedules to scan, or `None` to scan every active schedule.
+        #[ink(message)]
+        pub fn schedules_unlocking_between(
+            &self,
+            start: Timestamp,
+            end: Timestamp,
+            page: Option<(u32, u32)>
+        ) -> Vec<(u64, AccountId, Balance)> {
+            let (first, last) = match page {
+                Some((position, len)) => {
+                    let position = u64::from(position);
+                    (position, position.saturating_add(u64::from(len)).min(self.schedule_count))
+                }
+                None => (0, self.schedule_count),
+            };
+            (first..last)
+                .filter_map(|position| self.active_ids.get(position))
+                .filter_map(|id| self.load_schedule(id).map(|schedule| (id, schedule)))
+                .filter(|(_, schedule)| {
+                    let unlock_time = schedule.kind.unlock_time();
+                    unlock_time.is_some_and(|unlock_time| (start..=end).contains(&unlock_time))
+                })
+                .map(|(id, schedule)| (id, schedule.beneficiary, schedule.amount - schedule.released))
+                .collect()
+        }
+
+        /// Returns the IDs of the schedules created by `owner` that are still active.
+        ///
+        /// # Arguments
+        ///
+        /// * `owner`: The account that created the schedules.
+        #[ink(message)]
+        pub fn get_owned_schedule_ids(&self, owner: AccountId) -> Vec<u64> {
+            self.owner_to_ids.get(owner).unwrap_or_default()
+        }
+
+        /// Returns the earliest time at which one of the beneficiary's schedules becomes
+        /// fully vested, or `None` if no schedule is still locked.
+        ///
+        /// # Arguments
+        ///
+        /// * `beneficiary`: The account whose schedules are inspected.
+        #[ink(message)]
+        pub fn next_unlock(&self, beneficiary: AccountId) -> Option<Timestamp> {
+            let current_time: Timestamp = self.env().block_timestamp();
+            self.beneficiary_to_ids
+                .get(beneficiary)
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|id| self.load_schedule(id))
+                .filter_map(|schedule| schedule.kind.unlock_time())
+                .filter(|&unlock_time| unlock_time > current_time)
+                .min()
+        }
+
+        /// Returns the earliest time at which one of the beneficiary's schedules unlocks
+        /// funds, along with the amount unlocking at that time, or `None` if no schedule
+        /// is still locked. Tranche schedules unlock their next tranche, other schedules
+        /// unlock everything still locked once fully vested.
+        ///
+        /// # Arguments
+        ///
+        /// * `beneficiary`: The account whose schedules are inspected.
+        #[ink(message)]
+        pub fn next_unlock_detail(&self, beneficiary: AccountId) -> Option<(Timestamp, Balance)> {
+            let current_time: Timestamp = self.env().block_timestamp();
+            let current_block = self.env().block_number();
+            let mut next: Option<(Timestamp, Balance)> = None;
+            for id in self.beneficiary_to_ids.get(beneficiary).unwrap_or_default() {
+                let Some(schedule) = self.load_schedule(id) else {
+                    continue;
+                };
+                let (unlock_time, locked) = match &schedule.kind {
+                    // Only the earliest future tranche unlocks next
+                    VestingKind::Tranches { tranches } => {
+                        let Some(&tranche) = tranches.iter().find(|&&(time, _)| time > current_time) else {
+                            continue;
+                        };
+                        tranche
+                    }
+                    kind => {
+                        // Block schedules have no unlock time to compare
+                        let Some(unlock_time) = kind.unlock_time() else {
+                            continue;
+                        };
+                        if unlock_time <= current_time {
+                            continue;
+                        }
+                        (unlock_time, schedule.amount - schedule.vested_amount(current_time, current_block))
+                    }
+                };
+
+                // Schedules unlocking at the same earliest time are added up
+                next = match next {
+                    Some((time, amount)) if time == unlock_time => {
+                        Some((time, amount.saturating_add(locked)))
+                    }
+                    Some((time, _)) if time < unlock_time => next,
+                    _ => Some((unlock_time, locked)),
+                };
+            }
+            next
+        }
+
+        /// Returns the IDs of the schedules `withdraw_fund` would release right now,
+        /// without changing any state.
+        ///
+        /// # Arguments
+        ///
+        /// * `beneficiary`: The account whose schedules are inspected.
+        #[ink(message)]
+        pub fn withdrawable_ids(&self, beneficiary: AccountId) -> Vec<u64> {
+            let current_time: Timestamp = self.env().block_timestamp();
+            let current_block = self.env().block_number();
+            let mut seen_ids = BTreeSet::new();
+            self.beneficiary_to_ids
+                .get(beneficiary)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|&id| seen_ids.insert(id))
+                .filter(|&id| {
+                    self.load_schedule(id)
+                        .is_some_and(|schedule| schedule.claimable(current_time, current_block) > 0)
+                })
+                .collect()
+        }
+
+        /// Returns the amount `withdraw_fund` would pay to `beneficiary` right now, after
+        /// the protocol fee, without changing any state.
+        ///
+        /// # Arguments
+        ///
+        /// * `beneficiary`: The account whose withdrawal is simulated.
+        ///
+        /// # Errors
+        ///
+        /// Returns the error `withdraw_fund` would fail with:
+        /// `Error::NoSchedules`, `Error::CooldownActive`, `Error::Paused`,
+        /// `Error::AmountOverflow` or `Error::NoFundsAvailable`.
+        #[ink(message)]
+        pub fn simulate_withdraw(&self, beneficiary: AccountId) -> Result<Balance> {
+            let current_time: Timestamp = self.env().block_timestamp();
+            let current_block = self.env().block_number();
+            let total_amount = self.withdrawable_amount(beneficiary, current_time, current_block)?;
+            Ok(total_amount - self.protocol_fee(total_amount))
+        }
+
+        /// Returns the time and amount paid of the most recent claims of a beneficiary,
+        /// oldest first. At most the last 50 claims are kept.
+        ///
+        /// # Arguments
+        ///
+        /// * `beneficiary`: The account whose claims are returned.
+        #[ink(message)]
+        pub fn get_claim_history(&self, beneficiary: AccountId) -> ClaimHistory {
+            self.claim_history.get(beneficiary).unwrap_or_default()
+        }
+
+        /// Returns the sum of all the funds ever paid to a beneficiary, after the
+        /// protocol fee.
+        ///
+        /// # Arguments
+        ///
+        /// * `beneficiary`: The account whose total is returned.
+        #[ink(message)]
+        pub fn total_received_of(&self, beneficiary: AccountId) -> Balance {
+            self.total_received.get(beneficiary).unwrap_or_default()
+        }
+
+        /// Returns the average time (in milliseconds) between the unlock time of a
+        /// beneficiary's schedules and the claim that fully released them, or `None` if
+        /// no schedule was fully claimed yet. Block schedules are not counted.
+        ///
+        /// # Arguments
+        ///
+        /// * `beneficiary`: The account whose claims are inspected.
+        #[ink(message)]
+        pub fn average_claim_delay(&self, beneficiary: AccountId) -> Option<u64> {
+            let (total_delay, count) = self.claim_delays.get(beneficiary)?;
+            total_delay.checked_div(count)
+        }
+
+        /// Returns everything a front-end shows about a beneficiary in a single call:
+        /// the `balances_of` split, `next_unlock`, `schedule_count` and `total_received_of`.
+        ///
+        /// # Arguments
+        ///
+        /// * `beneficiary`: The account whose funds are reported.
+        #[ink(message)]
+        pub fn dashboard(&self, beneficiary: AccountId) -> BeneficiaryDashboard {
+            let (claimable, locked) = self.balances_of(beneficiary);
+            BeneficiaryDashboard {
+                claimable,
+                locked,
+                next_unlock: self.next_unlock(beneficiary),
+                schedule_count: self.schedule_count(beneficiary),
+                total_received: self.total_received_of(beneficiary),
+            }
+        }
+
+        //----------------------------------
+        // Administration
+        //----------------------------------
+        /// Freeze deposits and withdrawals, e.g. while an exploit is investigated.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::NotAdmin` if the caller is not the admin.
+        #[ink(message)]
+        pub fn pause(&mut self) -> Result<()> {
+            self.ensure_admin()?;
+            self.config_changed(ConfigParam::Paused, self.paused.into(), 1);
+            self.paused = true;
+            Ok(())
+        }
+
+        /// Resume deposits and withdrawals.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::NotAdmin` if the caller is not the admin.
+        #[ink(message)]
+        pub fn unpause(&mut self) -> Result<()> {
+            self.ensure_admin()?;
+            self.config_changed(ConfigParam::Paused, self.paused.into(), 0);
+            self.paused = false;
+            Ok(())
+        }
+
+        /// Freeze new deposits while letting beneficiaries keep withdrawing.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::NotAdmin` if the caller is not the admin.
+        #[ink(message)]
+        pub fn freeze_deposits(&mut self) -> Result<()> {
+            self.ensure_admin()?;
+            self.config_changed(ConfigParam::DepositsFrozen, self.deposits_frozen.into(), 1);
+            self.deposits_frozen = true;
+            Ok(())
+        }
+
+        /// Accept new deposits again.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::NotAdmin` if the caller is not the admin.
+        #[ink(message)]
+        pub fn unfreeze_deposits(&mut self) -> Result<()> {
+            self.ensure_admin()?;
+            self.config_changed(ConfigParam::DepositsFrozen, self.deposits_frozen.into(), 0);
+            self.deposits_frozen = false;
+            Ok(())
+        }
+
+        /// Set the protocol fee deducted from every withdrawal.
+        ///
+        /// # Arguments
+        ///
+        /// * `fee_bps`: The fee in basis points of the withdrawn amount, at most 1000 (10%).
+        /// * `fee_recipient`: The account receiving the fee.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::NotAdmin` if the caller is not the admin.
+        /// Returns `Error::FeeTooHigh` if `fee_bps` is above 1000.
+        #[ink(message)]
+        pub fn set_fee(&mut self, fee_bps: u16, fee_recipient: AccountId) -> Result<()> {
+            self.ensure_admin()?;
+            if fee_bps > MAX_FEE_BPS {
+                return Err(Error::FeeTooHigh);
+            }
+            self.config_account_changed(
+                ConfigParam::FeeRecipient,
+                Some(self.fee_recipient),
+                Some(fee_recipient),
+            );
+            self.config_changed(ConfigParam::FeeBps, self.fee_bps.into(), fee_bps.into());
+            self.fee_bps = fee_bps;
+            self.fee_recipient = fee_recipient;
+            Ok(())
+        }
+
+        /// Set the minimum time between two withdrawals of the same beneficiary.
+        ///
+        /// # Arguments
+        ///
+        /// * `cooldown_ms`: The cooldown in milliseconds, 0 to disable it.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::NotAdmin` if the caller is not the admin.
+        #[ink(message)]
+        pub fn set_cooldown(&mut self, cooldown_ms: Timestamp) -> Result<()> {
+            self.ensure_admin()?;
+            self.config_changed(ConfigParam::CooldownMs, self.cooldown_ms.into(), cooldown_ms.into());
+            self.cooldown_ms = cooldown_ms;
+            Ok(())
+        }
+
+        /// Set the contract notified after every withdrawal.
+        ///
+        /// The contract receives an `on_withdraw(beneficiary, amount)` call. A failing
+        /// notification does not block the withdrawal.
+        ///
+        /// # Arguments
+        ///
+        /// * `notify_contract`: The contract to notify, `None` to stop notifications.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::NotAdmin` if the caller is not the admin.
+        #[ink(message)]
+        pub fn set_notify_contract(&mut self, notify_contract: Option<AccountId>) -> Result<()> {
+            self.ensure_admin()?;
+            self.config_account_changed(ConfigParam::NotifyContract, self.notify_contract, notify_contract);
+            self.notify_contract = notify_contract;
+            Ok(())
+        }
+
+        /// Set the maximum number of schedules a beneficiary can have.
+        ///
+        /// Bounds the work done by a full withdrawal and prevents owners from
+        /// flooding a beneficiary with schedules. Existing schedules are kept.
+        ///
+        /// # Arguments
+        ///
+        /// * `max`: The maximum number of schedules, 0 for no limit.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::NotAdmin` if the caller is not the admin.
+        #[ink(message)]
+        pub fn set_max_schedules_per_beneficiary(&mut self, max: u32) -> Result<()> {
+            self.ensure_admin()?;
+            self.config_changed(
+                ConfigParam::MaxSchedulesPerBeneficiary,
+                self.max_schedules_per_beneficiary.into(),
+                max.into()
+            );
+            self.max_schedules_per_beneficiary = max;
+            Ok(())
+        }
+
+        /// Set the largest amount a single schedule can hold. Existing schedules are kept.
+        ///
+        /// # Arguments
+        ///
+        /// * `max`: The maximum amount of a schedule, 0 for no limit.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::NotAdmin` if the caller is not the admin.
+        #[ink(message)]
+        pub fn set_max_schedule_amount(&mut self, max: Balance) -> Result<()> {
+            self.ensure_admin()?;
+            self.config_changed(ConfigParam::MaxScheduleAmount, self.max_schedule_amount, max);
+            self.max_schedule_amount = max;
+            Ok(())
+        }
+
+        /// Require the co-signer's approval to revoke schedules refunding more than
+        /// `threshold`.
+        ///
+        /// # Arguments
+        ///
+        /// * `threshold`: The largest refund allowed without approval, 0 to never require one.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::NotAdmin` if the caller is not the admin.
+        #[ink(message)]
+        pub fn set_large_amount_threshold(&mut self, threshold: Balance) -> Result<()> {
+            self.ensure_admin()?;
+            self.config_changed(ConfigParam::LargeAmountThreshold, self.large_amount_threshold, threshold);
+            self.large_amount_threshold = threshold;
+            Ok(())
+        }
+
+        /// Cap the funds withdrawn from all schedules within each window of `window_ms`,
+        /// as a circuit breaker. A window starts with the first withdrawal after the
+        /// previous one ended.
+        ///
+        /// # Arguments
+        ///
+        /// * `max_outflow_per_window`: The most funds withdrawn per window, 0 for no cap.
+        /// * `window_ms`: The length of a window in milliseconds.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::NotAdmin` if the caller is not the admin.
+        #[ink(message)]
+        pub fn set_outflow_cap(
+            &mut self,
+            max_outflow_per_window: Balance,
+            window_ms: Timestamp
+        ) -> Result<()> {
+            self.ensure_admin()?;
+            self.config_changed(
+                ConfigParam::MaxOutflowPerWindow,
+                self.max_outflow_per_window,
+                max_outflow_per_window
+            );
+            self.config_changed(
+                ConfigParam::OutflowWindowMs,
+                self.outflow_window_ms.into(),
+                window_ms.into()
+            );
+            self.max_outflow_per_window = max_outflow_per_window;
+            self.outflow_window_ms = window_ms;
+            Ok(())
+        }
+
+        /// Restrict the creation of schedules to the whitelisted owners, or lift the
+        /// restriction.
+        ///
+        /// # Arguments
+        ///
+        /// * `enabled`: Whether only whitelisted owners can create schedules.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::NotAdmin` if the caller is not the admin.
+        #[ink(message)]
+        pub fn set_whitelist_enabled(&mut self, enabled: bool) -> Result<()> {
+            self.ensure_admin()?;
+            self.config_changed(ConfigParam::WhitelistEnabled, self.whitelist_enabled.into(), enabled.into());
+            self.whitelist_enabled = enabled;
+            Ok(())
+        }
+
+        /// Allow `owner` to create schedules while the whitelist is enabled.
+        ///
+        /// # Arguments
+        ///
+        /// * `owner`: The account to add to the whitelist.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::NotAdmin` if the caller is not the admin.
+        #[ink(message)]
+        pub fn add_allowed_owner(&mut self, owner: AccountId) -> Result<()> {
+            self.ensure_admin()?;
+            self.owner_whitelist.insert(owner, &true);
+            Ok(())
+        }
+
+        /// Remove `owner` from the whitelist. Their existing schedules are not affected.
+        ///
+        /// # Arguments
+        ///
+        /// * `owner`: The account to remove from the whitelist.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::NotAdmin` if the caller is not the admin.
+        #[ink(message)]
+        pub fn remove_allowed_owner(&mut self, owner: AccountId) -> Result<()> {
+            self.ensure_admin()?;
+            self.owner_whitelist.remove(owner);
+            Ok(())
+        }
+
+        /// Returns whether `owner` is on the whitelist of accounts allowed to create
+        /// schedules, whether or not the whitelist is enabled.
+        #[ink(message)]
+        pub fn is_allowed_owner(&self, owner: AccountId) -> bool {
+            self.owner_whitelist.get(owner).unwrap_or_default()
+        }
+
+        /// Set the account approving large revocations.
+        ///
+        /// # Arguments
+        ///
+        /// * `co_signer`: The approving account, `None` to remove it.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::NotAdmin` if the caller is not the admin.
+        #[ink(message)]
+        pub fn set_co_signer(&mut self, co_signer: Option<AccountId>) -> Result<()> {
+            self.ensure_admin()?;
+            self.config_account_changed(ConfigParam::CoSigner, self.co_signer, co_signer);
+            self.co_signer = co_signer;
+            Ok(())
+        }
+
+        /// Approve the revocation of a large schedule as the co-signer.
+        ///
+        /// # Arguments
+        ///
+        /// * `id`: The ID of the schedule whose revocation is approved.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::NotCoSigner` if the caller is not the co-signer.
+        /// Returns `Error::ScheduleNotFound` if no schedule exists for `id`.
+        #[ink(message)]
+        pub fn approve_revoke(&mut self, id: u64) -> Result<()> {
+            if self.co_signer != Some(self.env().caller()) {
+                return Err(Error::NotCoSigner);
+            }
+            if !self.schedules.contains(id) {
+                return Err(Error::ScheduleNotFound);
+            }
+            self.revoke_approvals.insert(id, &true);
+            Ok(())
+        }
+
+        /// Rebuild the schedule list of a beneficiary from a set of candidate IDs.
+        ///
+        /// This is a recovery tool for an index that drifted from the stored schedules:
+        /// only the candidates whose schedule exists and belongs to `beneficiary` are kept.
+        ///
+        /// # Arguments
+        ///
+        /// * `beneficiary`: The account whose schedule list is rebuilt.
+        /// * `candidate_ids`: The IDs that may belong to `beneficiary`.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::NotAdmin` if the caller is not the admin.
+        #[ink(message)]
+        pub fn rebuild_beneficiary_index(
+            &mut self,
+            beneficiary: AccountId,
+            candidate_ids: Vec<u64>
+        ) -> Result<()> {
+            self.ensure_admin()?;
+
+            let mut seen_ids = BTreeSet::new();
+            let ids: Vec<u64> = candidate_ids
+                .into_iter()
+                .filter(|&id| seen_ids.insert(id))
+                .filter(|&id| {
+                    self.load_schedule(id)
+                        .is_some_and(|schedule| schedule.beneficiary == beneficiary)
+                })
+                .collect();
+            self.store_beneficiary_ids(beneficiary, &ids);
+            Ok(())
+        }
+
+        /// Move a schedule to a new owner, e.g. after the granting account migrated.
+        ///
+        /// This is a recovery tool: the new owner gets every right of the old one over
+        /// the schedule, including revoking it and receiving the refund.
+        ///
+        /// # Arguments
+        ///
+        /// * `id`: The ID of the schedule.
+        /// * `new_owner`: The account that will own the schedule.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::NotAdmin` if the caller is not the admin.
+        /// Returns `Error::ScheduleNotFound` if no schedule exists for `id`.
+        #[ink(message)]
+        pub fn reassign_owner(&mut self, id: u64, new_owner: AccountId) -> Result<()> {
+            self.ensure_admin()?;
+
+            let mut schedule = self.load_schedule(id).ok_or(Error::ScheduleNotFound)?;
+
+            let old_owner = schedule.owner;
+            self.remove_owner_id(old_owner, id);
+            self.add_owner_id(new_owner, id);
+
+            // The co-signer approved the revocation for the previous owner
+            self.revoke_approvals.remove(id);
+
+            schedule.owner = new_owner;
+            self.store_schedule(id, &schedule);
+
+            self.env().emit_event(OwnerReassigned {
+                id,
+                old_owner,
+                new_owner,
+            });
+
+            Ok(())
+        }
+
+        /// Remove the given IDs from the active schedule index if their schedule no
+        /// longer exists.
+        ///
+        /// This is a housekeeping tool for index entries left behind by a removed
+        /// schedule. IDs of stored schedules and IDs missing from the index are ignored.
+        ///
+        /// Returns the number of pruned entries.
+        ///
+        /// # Arguments
+        ///
+        /// * `ids`: The IDs to check.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::NotAdmin` if the caller is not the admin.
+        #[ink(message)]
+        pub fn prune(&mut self, ids: Vec<u64>) -> Result<u32> {
+            self.ensure_admin()?;
+
+            let mut pruned: u32 = 0;
+            for id in ids {
+                if !self.schedules.contains(id) && self.remove_active_id(id) {
+                    self.schedule_count = self.schedule_count.saturating_sub(1);
+                    pruned = pruned.saturating_add(1);
+                }
+            }
+            Ok(pruned)
+        }
+
+        /// Send the native balance that no schedule or credit accounts for to the admin,
+        /// e.g. value transferred to the contract outside of a deposit.
+        ///
+        /// Returns the amount swept.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::NotAdmin` if the caller is not the admin.
+        /// Returns `Error::NoFundsAvailable` if there is no untracked balance.
+        /// Returns `Error::TransferFailed` if the transfer to the admin fails.
+        #[ink(message)]
+        pub fn sweep_untracked(&mut self) -> Result<Balance> {
+            self.ensure_admin()?;
+
+            // With a PSP22 token the vested funds are not held in the native balance
+            let tracked = match self.mode {
+                VestingMode::Token(_) => 0,
+                VestingMode::Native => self.total_locked.saturating_add(self.total_credited),
+            };
+            let untracked = self.env().balance().saturating_sub(tracked);
+            if untracked == 0 {
+                return Err(Error::NoFundsAvailable);
+            }
+
+            self.env().transfer(self.admin, untracked).map_err(|_| Error::TransferFailed)?;
+
+            Ok(untracked)
+        }
+
+        /// Request an emergency withdrawal of a schedule's funds to the admin.
+        ///
+        /// The withdrawal can only be executed with `emergency_withdraw_execute` after
+        /// a delay of 7 days, which gives the beneficiary time to react.
+        ///
+        /// # Arguments
+        ///
+        /// * `id`: The ID of the schedule.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::NotAdmin` if the caller is not the admin.
+        /// Returns `Error::ScheduleNotFound` if the schedule does not exist.
+        #[ink(message)]
+        pub fn emergency_withdraw_request(&mut self, id: u64) -> Result<()> {
+            self.ensure_admin()?;
+            if !self.schedules.contains(id) {
+                return Err(Error::ScheduleNotFound);
+            }
+
+            let current_time: Timestamp = self.env().block_timestamp();
+            self.emergency_requests.insert(id, &current_time);
+
+            self.env().emit_event(EmergencyWithdrawRequested {
+                id,
+                execute_after: current_time.saturating_add(EMERGENCY_WITHDRAW_DELAY),
+            });
+
+            Ok(())
+        }
+
+        /// Execute a previously requested emergency withdrawal, sending the funds
+        /// still held by the schedule to the admin and removing the schedule.
+        ///
+        /// # Arguments
+        ///
+        /// * `id`: The ID of the schedule.
+        ///
+        /// Returns the amount sent to the admin.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::NotAdmin` if the caller is not the admin.
+        /// Returns `Error::TimelockNotElapsed` if no withdrawal was requested for the
+        /// schedule or the delay has not elapsed yet.
+        /// Returns `Error::ScheduleNotFound` if the schedule does not exist anymore.
+        /// Returns `Error::TransferFailed` if the transfer to the admin fails.
+        #[ink(message)]
+        pub fn emergency_withdraw_execute(&mut self, id: u64) -> Result<Balance> {
+            self.ensure_admin()?;
+            let current_time: Timestamp = self.env().block_timestamp();
+
+            let requested_at = self.emergency_requests.get(id).ok_or(Error::TimelockNotElapsed)?;
+            if current_time < requested_at.saturating_add(EMERGENCY_WITHDRAW_DELAY) {
+                return Err(Error::TimelockNotElapsed);
+            }
+
+            let schedule = self.load_schedule(id).ok_or(Error::ScheduleNotFound)?;
+            let amount = schedule.amount - schedule.released;
+
+            // Remove the schedule before sending the funds
+            self.emergency_requests.remove(id);
+            self.remove_schedule(id);
+            self.remove_beneficiary_id(schedule.beneficiary, id);
+            self.remove_owner_id(schedule.owner, id);
+            self.unlock_funds(schedule.beneficiary, amount);
+
+            let admin = self.admin;
+            self.transfer_out(admin, amount)?;
+
+            self.env().emit_event(EmergencyWithdrawn { id, admin, amount });
+
+            Ok(amount)
+        }
+
+        /// Propose a new admin. The change only takes effect once the proposed
+        /// account calls `accept_admin`.
+        ///
+        /// # Arguments
+        ///
+        /// * `new_admin`: The account proposed as the next admin.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::NotAdmin` if the caller is not the admin.
+        #[ink(message)]
+        pub fn transfer_admin(&mut self, new_admin: AccountId) -> Result<()> {
+            self.ensure_admin()?;
+            self.pending_admin = Some(new_admin);
+            Ok(())
+        }
+
+        /// Accept the admin role proposed to the caller.
+        ///
+        /// # Errors
+        ///
+        /// Returns `Error::NotPendingAdmin` if the caller is not the proposed admin.
+        #[ink(message)]
+        pub fn accept_admin(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+            if self.pending_admin != Some(caller) {
+                return Err(Error::NotPendingAdmin);
+            }
+
+            let old_admin = self.admin;
+            self.admin = caller;
+            self.pending_admin = None;
+
+            self.env().emit_event(AdminTransferred {
+                old_admin,
+                new_admin: caller,
+            });
+
+            Ok(())
+        }
+
+        /// Returns the account allowed to perform privileged actions.
+        #[ink(message)]
+        pub fn admin(&self) -> AccountId {
+            self.admin
+        }
+
+        /// Returns whether the contract vests the native balance or a PSP22 token.
+        #[ink(message)]
+        pub fn mode(&self) -> VestingMode {
+            self.mode
+        }
+
+        /// Returns whether deposits and withdrawals are frozen.
+        #[ink(message)]
+        pub fn is_paused(&self) -> bool {
+            self.paused
+        }
+
+        /// Returns the version of the storage layout.
+        #[ink(message)]
+        pub fn storage_version(&self) -> u16 {
+            self.version
+        }
+
+        /// Returns a human-readable description of an error, or an empty message if
+        /// `code` is not a known error.
+        ///
+        /// # Arguments
+        ///
+        /// * `code`: The discriminant of the error, as encoded in a failed call.
+        #[ink(message)]
+        pub fn error_message(&self, code: u8) -> Vec<u8> {
+            <Error as scale::Decode>::decode(&mut &[code][..])
+                .map(|error| error.message().as_bytes().to_vec())
+                .unwrap_or_default()
+        }
+
+        /// Returns whether this contract supports a feature, so integrators can detect
+        /// capabilities without parsing the metadata.
+        ///
+        /// # Arguments
+        ///
+        /// * `feature`: The ID of the feature, as listed in `Feature`.
+        #[ink(message)]
+        pub fn supports(&self, feature: u32) -> bool {
+            SUPPORTED_FEATURES.iter().any(|&supported| supported as u32 == feature)
+        }
+
+        //----------------------------------
+        // Internal Helpers
+        //----------------------------------
+        /// Fails with `Error::NotAdmin` unless the caller is the admin.
+        fn ensure_admin(&self) -> Result<()> {
+            if self.env().caller() != self.admin {
+                return Err(Error::NotAdmin);
+            }
+            Ok(())
+        }
+
+        /// Runs `f` while holding the reentrancy lock, failing with `Error::Reentrancy`
+        /// if the lock is already held. The lock is released on every exit path of `f`.
+        fn non_reentrant<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+            if self.reentrancy_lock.get().unwrap_or_default() {
+                return Err(Error::Reentrancy);
+            }
+
+            self.reentrancy_lock.set(&true);
+            let result = f(self);
+            self.reentrancy_lock.set(&false);
+            result
+        }
+
+        /// Fails with `Error::Paused` while the contract is paused.
+        fn ensure_not_paused(&self) -> Result<()> {
+            if self.paused {
+                return Err(Error::Paused);
+            }
+            Ok(())
+        }
+
+        /// Fails if `beneficiary` is the zero address or the contract itself.
+        fn ensure_valid_beneficiary(&self, beneficiary: AccountId) -> Result<()> {
+            if beneficiary == AccountId::from([0u8; 32]) || beneficiary == self.env().account_id() {
+                return Err(Error::InvalidBeneficiary);
+            }
+            Ok(())
+        }
+
+        /// Fails if refunding `amount` from schedule `id` is above the large amount
+        /// threshold and the co-signer has not approved it.
+        fn ensure_revoke_approved(&self, id: u64, amount: Balance) -> Result<()> {
+            let threshold = self.large_amount_threshold;
+            if threshold > 0 && amount > threshold && !self.revoke_approvals.get(id).unwrap_or_default() {
+                return Err(Error::RevokeNotApproved);
+            }
+            Ok(())
+        }
+
+        /// Fails if withdrawing `amount` at `now` would exceed the outflow cap.
+        fn ensure_outflow_allowed(&self, amount: Balance, now: Timestamp) -> Result<()> {
+            if self.max_outflow_per_window == 0 {
+                return Ok(());
+            }
+
+            // A new window starts once the current one is over
+            let window_ended = now >= self.outflow_window_start.saturating_add(self.outflow_window_ms);
+            let used = if window_ended { 0 } else { self.outflow_in_window };
+            if used.saturating_add(amount) > self.max_outflow_per_window {
+                return Err(Error::OutflowCapReached);
+            }
+            Ok(())
+        }
+
+        /// Adds `amount` withdrawn at `now` to the outflow of the current window,
+        /// starting a new window if the current one is over.
+        fn record_outflow(&mut self, amount: Balance, now: Timestamp) -> Result<()> {
+            if self.max_outflow_per_window == 0 {
+                return Ok(());
+            }
+
+            self.ensure_outflow_allowed(amount, now)?;
+            if now >= self.outflow_window_start.saturating_add(self.outflow_window_ms) {
+                self.outflow_window_start = now;
+                self.outflow_in_window = 0;
+            }
+            self.outflow_in_window = self.outflow_in_window.saturating_add(amount);
+            Ok(())
+        }
+
+        /// Fails if `beneficiary` already has the maximum number of schedules.
+        fn ensure_schedule_capacity(&self, beneficiary: AccountId) -> Result<()> {
+            let max = self.max_schedules_per_beneficiary;
+            if max > 0 && self.schedule_count(beneficiary) >= max {
+                return Err(Error::TooManySchedules);
+            }
+            Ok(())
+        }
+
+        /// Fails if the whitelist is enabled and `owner` is not on it.
+        fn ensure_whitelisted(&self, owner: AccountId) -> Result<()> {
+            if self.whitelist_enabled && !self.is_allowed_owner(owner) {
+                return Err(Error::NotWhitelisted);
+            }
+            Ok(())
+        }
+
+        /// Fails if a schedule of `amount` exceeds the maximum schedule amount.
+        fn ensure_schedule_amount(&self, amount: Balance) -> Result<()> {
+            let max = self.max_schedule_amount;
+            if max > 0 && amount > max {
+                return Err(Error::AmountTooLarge);
+            }
+            Ok(())
+        }
+
+        /// Returns the schedule stored under `id`, if any.
+        fn load_schedule(&self, id: u64) -> Option<VestingSchedule> {
+            self.schedules.get(id).map(VestingSchedule::from)
+        }
+
+        /// Stores `schedule` under `id`, replacing any previous one.
+        fn store_schedule(&mut self, id: u64, schedule: &VestingSchedule) {
+            self.schedules.insert(id, &StoredSchedule::from(schedule));
+        }
+
+        /// Emits `ConfigChanged` for `param`.
+        fn config_changed(&self, param: ConfigParam, old_value: u128, new_value: u128) {
+            self.env().emit_event(ConfigChanged {
+                param: param as u8,
+                old_value,
+                new_value,
+            });
+        }
+
+        /// Emits `ConfigAccountChanged` for `param`.
+        fn config_account_changed(
+            &self,
+            param: ConfigParam,
+            old_account: Option<AccountId>,
+            new_account: Option<AccountId>,
+        ) {
+            self.env().emit_event(ConfigAccountChanged {
+                param: param as u8,
+                old_account,
+                new_account,
+            });
+        }
+
+        /// Returns the value transferred with a native deposit, failing if the contract
+        /// vests a PSP22 token.
+        fn transferred_native(&self) -> Result<Balance> {
+            if self.mode != VestingMode::Native {
+                return Err(Error::WrongMode);
+            }
+            Ok(self.env().transferred_value())
+        }
+
+        /// Fails if the contract is paused or new deposits are frozen.
+        fn ensure_deposits_open(&self) -> Result<()> {
+            self.ensure_not_paused()?;
+            if self.deposits_frozen {
+                return Err(Error::DepositsFrozen);
+            }
+            Ok(())
+        }
+
+        /// Releases the available funds of every schedule of `beneficiary` and sends
+        /// them to `payout`, failing if there was nothing to release.
+        /// Returns the amount sent.
+        fn withdraw_all(&mut self, beneficiary: AccountId, payout: Payout) -> Result<Balance> {
+            self.withdraw_up_to(beneficiary, payout, None)
+        }
+
+        /// Releases at most `limit` of the available funds of `beneficiary`, all of them
+        /// if `None`, and sends them to `payout`, failing if there was nothing to release.
+        /// Returns the amount sent.
+        fn withdraw_up_to(
+            &mut self,
+            beneficiary: AccountId,
+            payout: Payout,
+            limit: Option<Balance>
+        ) -> Result<Balance> {
+            // Fail before touching storage if there is nothing to release
+            let current_time: Timestamp = self.env().block_timestamp();
+            let current_block = self.env().block_number();
+            let available = self.withdrawable_amount(beneficiary, current_time, current_block)?;
+            let limit = limit.unwrap_or(available);
+            if limit > available {
+                return Err(Error::ExceedsAvailable);
+            }
+            self.ensure_outflow_allowed(limit, current_time)?;
+
+            // Process every schedule of the beneficiary
+            let total_amount = self.non_reentrant(|this| {
+                this.withdraw_matured(beneficiary, payout, usize::MAX, limit)
+            })?;
+
+            self.notify_withdrawal(beneficiary, total_amount);
+
+            Ok(total_amount)
+        }
+
+        /// Fails if the last withdrawal of `beneficiary` is more recent than the cooldown.
+        fn ensure_cooldown_elapsed(&self, beneficiary: AccountId, current_time: Timestamp) -> Result<()> {
+            // Space out the withdrawals of a beneficiary when a cooldown is set
+            if self.cooldown_ms > 0 {
+                if let Some(last_withdraw) = self.last_withdraw.get(beneficiary) {
+                    if current_time.saturating_sub(last_withdraw) < self.cooldown_ms {
+                        return Err(Error::CooldownActive);
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        /// Starts the cooldown of `beneficiary` at `current_time`, if a cooldown is set.
+        fn start_cooldown(&mut self, beneficiary: AccountId, current_time: Timestamp) {
+            if self.cooldown_ms > 0 {
+                self.last_withdraw.insert(beneficiary, &current_time);
+            }
+        }
+
+        /// Returns the amount `withdraw_all` would release for `beneficiary` at
+        /// `current_time` and `current_block`, before the protocol fee, or the error it
+        /// would fail with.
+        fn withdrawable_amount(
+            &self,
+            beneficiary: AccountId,
+            current_time: Timestamp,
+            current_block: BlockNumber
+        ) -> Result<Balance> {
+            // Tell apart accounts without schedules from schedules that are still locked
+            let ids = self.beneficiary_to_ids.get(beneficiary).ok_or(Error::NoSchedules)?;
+
+            self.ensure_cooldown_elapsed(beneficiary, current_time)?;
+            self.ensure_not_paused()?;
+
+            let mut total_amount: Balance = 0;
+            let mut seen_ids = BTreeSet::new();
+            for id in ids {
+                // A duplicated ID must not be counted twice
+                if !seen_ids.insert(id) {
+                    continue;
+                }
+                if let Some(schedule) = self.load_schedule(id) {
+                    total_amount = total_amount
+                        .checked_add(schedule.claimable(current_time, current_block))
+                        .ok_or(Error::AmountOverflow)?;
+                }
+            }
+
+            // Check if any funds are available
+            if total_amount == 0 {
+                return Err(Error::NoFundsAvailable);
+            }
+
+            Ok(total_amount)
+        }
+
+        /// Releases the available funds of the first `max` schedules of `beneficiary`,
+        /// up to `limit`, and sends them to `payout`. Returns the amount sent.
+        ///
+        /// Follows checks-effects-interactions: every record is updated before any funds
+        /// leave the contract, so a call re-entering during the transfer already sees
+        /// the withdrawal as done.
+        fn withdraw_matured(
+            &mut self,
+            beneficiary: AccountId,
+            payout: Payout,
+            max: usize,
+            limit: Balance
+        ) -> Result<Balance> {
+            // Checks and effects
+            let (released, paid, ids) = self.settle_matured(beneficiary, payout, max, limit)?;
+            if released == 0 {
+                return Ok(0);
+            }
+
+            // Interactions, nothing but credits for failed transfers is written from here
+            self.send_payout(beneficiary, payout, released, &ids)?;
+            Ok(paid)
+        }
+
+        /// Marks the available funds of the first `max` schedules of `beneficiary`, up
+        /// to `limit`, as released and records the withdrawal to `payout`, without
+        /// sending anything. Returns the amount released, the amount `payout` gets and
+        /// the IDs of the schedules the funds come from.
+        fn settle_matured(
+            &mut self,
+            beneficiary: AccountId,
+            payout: Payout,
+            max: usize,
+            limit: Balance
+        ) -> Result<(Balance, Balance, Vec<u64>)> {
+            self.ensure_not_paused()?;
+            let current_time: Timestamp = self.env().block_timestamp();
+            let current_block = self.env().block_number();
+
+            // Retrieve the schedule IDs to process in this call, oldest first so that
+            // a limited withdrawal is taken from the oldest schedules
+            let ids = self.beneficiary_to_ids.get(beneficiary).unwrap_or_default();
+            let (batch, unprocessed) = ids.split_at(max.min(ids.len()));
+            let mut batch = batch.to_vec();
+            batch.sort_unstable();
+            let mut total_amount: u128 = 0;
+            let mut kept_ids = Vec::new();
+            let mut released_ids = Vec::new();
+            let mut seen_ids = BTreeSet::new();
+
+            // Process each schedule
+            for id in batch {
+                // A duplicated ID must not be processed, nor kept, twice
+                if !seen_ids.insert(id) {
+                    continue;
+                }
+                // An ID without a schedule is a stale index entry, it is dropped
+                let Some(mut schedule) = self.load_schedule(id) else {
+                    continue;
+                };
+
+                // Only the portion vested since the last withdrawal is claimable
+                let remaining = limit - total_amount;
+                let claimable = self.release(id, &mut schedule, current_time, current_block, remaining);
+                if claimable > 0 {
+                    total_amount = total_amount
+                        .checked_add(claimable)
+                        .ok_or(Error::AmountOverflow)?;
+                    released_ids.push(id);
+                }
+
+                // Keep schedules that still hold locked funds
+                if schedule.released < schedule.amount {
+                    kept_ids.push(id);
+                }
+            }
+
+            // Update remaining schedule IDs, unprocessed ones first
+            let mut remaining_ids = unprocessed.to_vec();
+            remaining_ids.extend(kept_ids);
+            self.store_beneficiary_ids(beneficiary, &remaining_ids);
+
+            if total_amount == 0 {
+                return Ok((0, 0, Vec::new()));
+            }
+            self.record_outflow(total_amount, current_time)?;
+
+            // Something is released, so start the cooldown before any funds leave the contract
+            self.start_cooldown(beneficiary, current_time);
+
+            let paid = self.net_payout(payout, total_amount);
+            self.record_claim(beneficiary, current_time, paid);
+
+            self.env().emit_event(Withdrawn {
+                beneficiary,
+                total_amount,
+                schedule_ids: released_ids.clone(),
+            });
+
+            Ok((total_amount, paid, released_ids))
+        }
+
+        /// Sends `amount` released from the schedules `ids` of `beneficiary` to `payout`.
+        fn send_payout(
+            &mut self,
+            beneficiary: AccountId,
+            payout: Payout,
+            amount: Balance,
+            ids: &[u64]
+        ) -> Result<()> {
+            match payout {
+                Payout::Transfer(recipient) => self.pay_out(recipient, amount, ids),
+                Payout::Stake(staking_contract) => self.stake_out(staking_contract, beneficiary, amount)?,
+                Payout::Relay { relayer, tip_bps } => self.relay_out(relayer, tip_bps, beneficiary, amount),
+            }
+            Ok(())
+        }
+
+        /// Marks the claimable portion of a schedule at `now` and `current_block`, up to
+        /// `max`, as released and stores the result, removing the schedule once it is
+        /// fully released. Returns the newly released amount.
+        fn release(
+            &mut self,
+            id: u64,
+            schedule: &mut VestingSchedule,
+            now: Timestamp,
+            current_block: BlockNumber,
+            max: Balance
+        ) -> Balance {
+            let claimable = schedule.claimable(now, current_block).min(max);
+            if claimable == 0 {
+                return 0;
+            }
+
+            // Read before the released tranches are pruned
+            let unlock_time = schedule.kind.unlock_time();
+            schedule.released += claimable;
+            self.total_withdrawn = self.total_withdrawn.saturating_add(claimable);
+            if let VestingKind::Tranches { tranches } = &mut schedule.kind {
+                if let Some(&last_tranche) = tranches.last() {
+                    tranches.retain(|&(time, _)| time > now);
+                    if tranches.is_empty() {
+                        tranches.push(last_tranche);
+                    }
+                }
+            }
+            self.unlock_funds(schedule.beneficiary, claimable);
+            if schedule.released == schedule.amount {
+                // Fully released, remove schedule
+                if let Some(unlock_time) = unlock_time {
+                    self.record_claim_delay(schedule.beneficiary, now.saturating_sub(unlock_time));
+                }
+                self.remove_schedule(id);
+                self.remove_owner_id(schedule.owner, id);
+                self.env().emit_event(ScheduleClosed {
+                    id,
+                    beneficiary: schedule.beneficiary,
+                });
+            } else {
+                self.store_schedule(id, schedule);
+            }
+
+            claimable
+        }
+
+        /// Adds the delay between a schedule's unlock time and its final claim to the
+        /// totals of `beneficiary`.
+        fn record_claim_delay(&mut self, beneficiary: AccountId, delay: Timestamp) {
+            let (total_delay, count) = self.claim_delays.get(beneficiary).unwrap_or_default();
+            self.claim_delays.insert(
+                beneficiary,
+                &(total_delay.saturating_add(delay), count.saturating_add(1))
+            );
+        }
+
+        /// Appends a claim to the history of `beneficiary`, dropping the oldest entries
+        /// beyond `MAX_CLAIM_HISTORY`, and adds it to their lifetime total.
+        fn record_claim(&mut self, beneficiary: AccountId, time: Timestamp, amount: Balance) {
+            let mut history = self.claim_history.get(beneficiary).unwrap_or_default();
+            history.push((time, amount));
+            if history.len() > MAX_CLAIM_HISTORY {
+                history.drain(..history.len() - MAX_CLAIM_HISTORY);
+            }
+            self.claim_history.insert(beneficiary, &history);
+
+            let received = self.total_received.get(beneficiary).unwrap_or_default();
+            self.total_received.insert(beneficiary, &received.saturating_add(amount));
+        }
+
+        /// Removes the schedule `id` from storage, along with its revocation approval.
+        fn remove_schedule(&mut self, id: u64) {
+            self.schedules.remove(id);
+            self.revoke_approvals.remove(id);
+            self.remove_active_id(id);
+            self.schedule_count = self.schedule_count.saturating_sub(1);
+        }
+
+        /// Removes `id` from the active schedule index, without touching
+        /// `schedule_count`. Returns whether `id` was in the index.
+        fn remove_active_id(&mut self, id: u64) -> bool {
+            let Some(position) = self.active_positions.take(id) else {
+                return false;
+            };
+
+            // Move the last active ID into the freed position to keep positions contiguous
+            let last_position = self.schedule_count.saturating_sub(1);
+            if position != last_position {
+                if let Some(last_id) = self.active_ids.get(last_position) {
+                    self.active_ids.insert(position, &last_id);
+                    self.active_positions.insert(last_id, &position);
+                }
+            }
+            self.active_ids.remove(last_position);
+            true
+        }
+
+        /// Adds `amount` to the funds locked in the contract for `beneficiary`.
+        fn lock_funds(&mut self, beneficiary: AccountId, amount: Balance) -> Result<()> {
+            let locked = self.locked_by_beneficiary.get(beneficiary).unwrap_or_default();
+            let locked = add_to_counter(locked, amount)?;
+            let total_locked = add_to_counter(self.total_locked, amount)?;
+            self.locked_by_beneficiary.insert(beneficiary, &locked);
+            self.total_locked = total_locked;
+            Ok(())
+        }
+
+        /// Removes `amount` from the funds locked in the contract for `beneficiary`.
+        fn unlock_funds(&mut self, beneficiary: AccountId, amount: Balance) {
+            let locked = self.locked_by_beneficiary.get(beneficiary).unwrap_or_default();
+            self.locked_by_beneficiary.insert(beneficiary, &locked.saturating_sub(amount));
+            self.total_locked = self.total_locked.saturating_sub(amount);
+        }
+
+        /// Appends `id` to the schedule list of `beneficiary`.
+        fn add_beneficiary_id(&mut self, beneficiary: AccountId, id: u64) {
+            let mut ids = self.beneficiary_to_ids.get(beneficiary).unwrap_or_default();
+            // IDs are never reused, an existing entry means the index is already up to date
+            if ids.contains(&id) {
+                return;
+            }
+            ids.push(id);
+            self.beneficiary_to_ids.insert(beneficiary, &ids);
+        }
+
+        /// Removes `id` from the schedule list of `beneficiary`.
+        fn remove_beneficiary_id(&mut self, beneficiary: AccountId, id: u64) {
+            let mut ids = self.beneficiary_to_ids.get(beneficiary).unwrap_or_default();
+            ids.retain(|&other| other != id);
+            self.store_beneficiary_ids(beneficiary, &ids);
+        }
+
+        /// Stores the schedule list of `beneficiary`, dropping the entry once it is
+        /// empty so no storage is held for accounts without schedules.
+        fn store_beneficiary_ids(&mut self, beneficiary: AccountId, ids: &Vec<u64>) {
+            if ids.is_empty() {
+                self.beneficiary_to_ids.remove(beneficiary);
+            } else {
+                self.beneficiary_to_ids.insert(beneficiary, ids);
+            }
+        }
+
+        /// Appends `id` to the list of schedules created by `owner`.
+        fn add_owner_id(&mut self, owner: AccountId, id: u64) {
+            let mut ids = self.owner_to_ids.get(owner).unwrap_or_default();
+            ids.push(id);
+            self.owner_to_ids.insert(owner, &ids);
+        }
+
+        /// Removes `id` from the list of schedules created by `owner`, dropping the
+        /// entry once it is empty.
+        fn remove_owner_id(&mut self, owner: AccountId, id: u64) {
+            let mut ids = self.owner_to_ids.get(owner).unwrap_or_default();
+            ids.retain(|&other| other != id);
+            if ids.is_empty() {
+                self.owner_to_ids.remove(owner);
+            } else {
+                self.owner_to_ids.insert(owner, &ids);
+            }
+        }
+
+        /// Pays `amount` out of the contract to `to`, either in the native balance
+        /// or in the configured PSP22 token.
+        fn transfer_out(&self, to: AccountId, amount: Balance) -> Result<()> {
+            match self.mode {
+                VestingMode::Token(token) => {
+                    self.call_token(token, TokenCall::Transfer { to, value: amount })
+                }
+                VestingMode::Native => self.env().transfer(to, amount).map_err(|_| Error::TransferFailed),
+            }
+        }
+
+        /// Returns the part of `amount` released funds that `payout` gets, after the
+        /// protocol fee and any relayer tip, without sending anything.
+        fn net_payout(&self, payout: Payout, amount: Balance) -> Balance {
+            let amount = amount - self.protocol_fee(amount);
+            match payout {
+                Payout::Relay { tip_bps, .. } => amount - bps_of(amount, tip_bps),
+                Payout::Transfer(_) | Payout::Stake(_) => amount,
+            }
+        }
+
+        /// Pays funds released from the schedules `ids` out to `to` after deducting the
+        /// protocol fee, which is sent to the fee recipient. Accounts that enabled the
+        /// vest callback receive them through `on_vest_received`.
+        fn pay_out(&mut self, to: AccountId, amount: Balance, ids: &[u64]) {
+            let paid = self.take_fee(amount);
+            if !self.vest_callbacks.get(to).unwrap_or_default() {
+                self.pay_or_credit(to, paid);
+            } else if !self.invoke_vest_callback(to, ids, paid) {
+                self.credit(to, paid);
+            }
+        }
+
+        /// Stakes released funds on `staking_contract` on behalf of `beneficiary` after
+        /// deducting the protocol fee.
+        fn stake_out(
+            &mut self,
+            staking_contract: AccountId,
+            beneficiary: AccountId,
+            amount: Balance
+        ) -> Result<()> {
+            let staked = self.take_fee(amount);
+            if !self.invoke_stake(staking_contract, beneficiary, staked) {
+                return Err(Error::StakeFailed);
+            }
+            Ok(())
+        }
+
+        /// Pays released funds out to `beneficiary` after deducting the protocol fee and
+        /// a tip of `tip_bps` for `relayer`.
+        fn relay_out(
+            &mut self,
+            relayer: AccountId,
+            tip_bps: u16,
+            beneficiary: AccountId,
+            amount: Balance
+        ) {
+            let amount = self.take_fee(amount);
+            let tip = bps_of(amount, tip_bps);
+            if tip > 0 {
+                self.pay_or_credit(relayer, tip);
+            }
+            self.pay_or_credit(beneficiary, amount - tip);
+        }
+
+        /// Sends the protocol fee on `amount` to the fee recipient.
+        /// Returns what is left of `amount`.
+        fn take_fee(&mut self, amount: Balance) -> Balance {
+            let fee = self.protocol_fee(amount);
+            if fee > 0 {
+                self.pay_or_credit(self.fee_recipient, fee);
+            }
+            amount - fee
+        }
+
+        /// Returns the protocol fee deducted from a payout of `amount`.
+        fn protocol_fee(&self, amount: Balance) -> Balance {
+            bps_of(amount, self.fee_bps)
+        }
+
+        /// Pays `amount` out to `to`, crediting it for a later `claim_credit` if the
+        /// transfer fails so a reverting recipient cannot block its own withdrawal.
+        fn pay_or_credit(&mut self, to: AccountId, amount: Balance) {
+            if self.transfer_out(to, amount).is_err() {
+                self.credit(to, amount);
+            }
+        }
+
+        /// Credits `amount` to `to` for a later `claim_credit`.
+        fn credit(&mut self, to: AccountId, amount: Balance) {
+            let credited = self.credited_balances.get(to).unwrap_or_default();
+            self.credited_balances.insert(to, &credited.saturating_add(amount));
+            self.total_credited = self.total_credited.saturating_add(amount);
+            self.env().emit_event(Credited {
+                beneficiary: to,
+                amount,
+            });
+        }
+
+        /// Performs `call` on the PSP22 `token` contract.
+        fn call_token(&self, token: AccountId, call: TokenCall) -> Result<()> {
+            match self.invoke_token(token, call) {
+                Some(Ok(())) => Ok(()),
+                _ => Err(Error::TokenCallFailed),
+            }
+        }
+
+        /// Dispatches `call` to the PSP22 `token` contract.
+        /// Returns `None` if the call could not be executed or decoded.
+        #[cfg(not(test))]
+        fn invoke_token(
+            &self,
+            token: AccountId,
+            call: TokenCall
+        ) -> Option<core::result::Result<(), PSP22Error>> {
+            use ink::env::call::{ build_call, ExecutionInput, Selector };
+
+            let result = match call {
+                TokenCall::Transfer { to, value } =>
+                    build_call::<ink::env::DefaultEnvironment>()
+                        .call(token)
+                        .exec_input(
+                            ExecutionInput::new(Selector::new(ink::selector_bytes!("PSP22::transfer")))
+                                .push_arg(to)
+                                .push_arg(value)
+                                .push_arg(Vec::<u8>::new())
+                        )
+                        .returns::<core::result::Result<(), PSP22Error>>()
+                        .try_invoke(),
+                TokenCall::TransferFrom { from, to, value } =>
+                    build_call::<ink::env::DefaultEnvironment>()
+                        .call(token)
+                        .exec_input(
+                            ExecutionInput::new(
+                                Selector::new(ink::selector_bytes!("PSP22::transfer_from"))
+                            )
+                                .push_arg(from)
+                                .push_arg(to)
+                                .push_arg(value)
+                                .push_arg(Vec::<u8>::new())
+                        )
+                        .returns::<core::result::Result<(), PSP22Error>>()
+                        .try_invoke(),
+            };
+
+            result.ok().and_then(|result| result.ok())
+        }
+
+        /// Dispatches `call` to the mocked PSP22 `token` contract, since the off-chain
+        /// test environment does not support cross-contract calls.
+        #[cfg(test)]
+        fn invoke_token(
+            &self,
+            token: AccountId,
+            call: TokenCall
+        ) -> Option<core::result::Result<(), PSP22Error>> {
+            Some(tests::mock_token::call(token, self.env().account_id(), call))
+        }
+
+        /// Queries the PSP22 `token` contract for the balance of this contract.
+        /// Returns `None` if the call could not be executed or decoded.
+        #[cfg(not(test))]
+        fn token_balance(&self, token: AccountId) -> Option<Balance> {
+            use ink::env::call::{ build_call, ExecutionInput, Selector };
+
+            build_call::<ink::env::DefaultEnvironment>()
+                .call(token)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!("PSP22::balance_of")))
+                        .push_arg(self.env().account_id())
+                )
+                .returns::<Balance>()
+                .try_invoke()
+                .ok()
+                .and_then(|result| result.ok())
+        }
+
+        /// Queries the mocked PSP22 `token` contract, since the off-chain test
+        /// environment does not support cross-contract calls.
+        #[cfg(test)]
+        fn token_balance(&self, _token: AccountId) -> Option<Balance> {
+            Some(tests::mock_token::balance_of(self.env().account_id()))
+        }
+
+        /// Calls `stake(beneficiary)` on `staking_contract` with `amount` attached.
+        /// Returns whether the call succeeded.
+        #[cfg(not(test))]
+        fn invoke_stake(&self, staking_contract: AccountId, beneficiary: AccountId, amount: Balance) -> bool {
+            use ink::env::call::{ build_call, ExecutionInput, Selector };
+
+            build_call::<ink::env::DefaultEnvironment>()
+                .call(staking_contract)
+                .transferred_value(amount)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!("stake"))).push_arg(beneficiary)
+                )
+                .returns::<()>()
+                .try_invoke()
+                .is_ok_and(|result| result.is_ok())
+        }
+
+        /// Calls the mocked staking contract, since the off-chain test environment
+        /// does not support cross-contract calls.
+        #[cfg(test)]
+        fn invoke_stake(&self, staking_contract: AccountId, beneficiary: AccountId, amount: Balance) -> bool {
+            tests::mock_stake::call(staking_contract, beneficiary, amount)
+        }
+
+        /// Calls `on_vest_received(ids, amount)` on `receiver` with `amount` attached.
+        /// Returns whether the call succeeded.
+        #[cfg(not(test))]
+        fn invoke_vest_callback(&self, receiver: AccountId, ids: &[u64], amount: Balance) -> bool {
+            use ink::env::call::{ build_call, ExecutionInput, Selector };
+
+            build_call::<ink::env::DefaultEnvironment>()
+                .call(receiver)
+                .transferred_value(amount)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!("on_vest_received")))
+                        .push_arg(ids)
+                        .push_arg(amount)
+                )
+                .returns::<()>()
+                .try_invoke()
+                .is_ok_and(|result| result.is_ok())
+        }
+
+        /// Calls the mocked receiving contract, since the off-chain test environment
+        /// does not support cross-contract calls.
+        #[cfg(test)]
+        fn invoke_vest_callback(&self, receiver: AccountId, ids: &[u64], amount: Balance) -> bool {
+            tests::mock_receiver::call(receiver, ids, amount)
+        }
+
+        /// Panics if `total_locked` does not match the funds still held by the active
+        /// schedules, or if the active schedule index is inconsistent.
+        #[cfg(test)]
+        fn assert_tvl_invariant(&self) {
+            let mut locked: Balance = 0;
+            for position in 0..self.schedule_count {
+                let id = self.active_ids.get(position).expect("Active position without an ID");
+                assert_eq!(self.active_positions.get(id), Some(position), "Stale position of schedule {id}");
+                let schedule = self.load_schedule(id).expect("Active ID without a schedule");
+                locked += schedule.amount - schedule.released;
+            }
+            assert_eq!(self.active_ids.get(self.schedule_count), None, "Active ID beyond the count");
+            assert_eq!(self.total_locked, locked, "Total locked does not match the active schedules");
+        }
+
+        /// Tells the notification contract, if any, that `beneficiary` withdrew `amount`.
+        /// A failed notification only emits `NotifyFailed`.
+        fn notify_withdrawal(&self, beneficiary: AccountId, amount: Balance) {
+            if let Some(notify_contract) = self.notify_contract {
+                if !self.invoke_notify(notify_contract, beneficiary, amount) {
+                    self.env().emit_event(NotifyFailed {
+                        notify_contract,
+                        beneficiary,
+                        amount,
+                    });
+                }
+            }
+        }
+
+        /// Calls `on_withdraw(beneficiary, amount)` on `notify_contract`.
+        /// Returns whether the call succeeded.
+        #[cfg(not(test))]
+        fn invoke_notify(&self, notify_contract: AccountId, beneficiary: AccountId, amount: Balance) -> bool {
+            use ink::env::call::{ build_call, ExecutionInput, Selector };
+
+            build_call::<ink::env::DefaultEnvironment>()
+                .call(notify_contract)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!("on_withdraw")))
+                        .push_arg(beneficiary)
+                        .push_arg(amount)
+                )
+                .returns::<()>()
+                .try_invoke()
+                .is_ok_and(|result| result.is_ok())
+        }
+
+        /// Calls the mocked notification contract, since the off-chain test
+        /// environment does not support cross-contract calls.
+        #[cfg(test)]
+        fn invoke_notify(&self, notify_contract: AccountId, beneficiary: AccountId, amount: Balance) -> bool {
+            tests::mock_notify::call(notify_contract, beneficiary, amount)
+        }
+
+        /// Creates one cliff schedule per `(beneficiary, unlock_time, amount)` entry,
+        /// checking that the amounts add up to the transferred value.
+        /// Returns the IDs of the new schedules.
+        fn create_schedules(
+            &mut self,
+            entries: Vec<(AccountId, Timestamp, Balance)>
+        ) -> Result<Vec<u64>> {
+            // The amounts must account for exactly the transferred value
+            let total = entries
+                .iter()
+                .try_fold(0u128, |total, &(_, _, amount)| total.checked_add(amount))
+                .ok_or(Error::AmountOverflow)?;
+            if total != self.transferred_native()? {
+                return Err(Error::AmountMismatch);
+            }
+
+            entries
+                .into_iter()
+                .map(|(beneficiary, unlock_time, amount)| {
+                    let kind = VestingKind::Cliff { unlock_time };
+                    self.create_schedule(beneficiary, amount, kind, None, true, false)
+                })
+                .collect()
+        }
+
+        /// Creates a new schedule of `amount` owned by the caller and indexes it
+        /// under the beneficiary. A schedule created `pending_acceptance` releases
+        /// nothing until the beneficiary accepts it. Returns the ID of the new schedule.
+        fn create_schedule(
+            &mut self,
+            beneficiary: AccountId,
+            amount: Balance,
+            kind: VestingKind,
+            label: Option<Vec<u8>>,
+            revocable: bool,
+            pending_acceptance: bool
+        ) -> Result<u64> {
+            self.ensure_valid_schedule(beneficiary, amount, &kind)?;
+            self.record_schedule(beneficiary, amount, kind, label, revocable, pending_acceptance)
+        }
+
+        /// Fails if the caller cannot create a schedule of `amount` with `kind` for
+        /// `beneficiary` right now.
+        fn ensure_valid_schedule(
+            &self,
+            beneficiary: AccountId,
+            amount: Balance,
+            kind: &VestingKind
+        ) -> Result<()> {
+            self.ensure_deposits_open()?;
+
+            // Get the caller
+            let owner = self.env().caller();
+            self.ensure_whitelisted(owner)?;
+
+            // Funds vested to these accounts could never be claimed
+            self.ensure_valid_beneficiary(beneficiary)?;
+            self.ensure_schedule_capacity(beneficiary)?;
+
+            // Prevent zero-value deposits
+            if amount == 0 {
+                return Err(Error::ZeroAmount);
+            }
+            self.ensure_schedule_amount(amount)?;
+
+            // The funds must not be withdrawable right away, nor locked for too long
+            let current_time: Timestamp = self.env().block_timestamp();
+            let unlock_time = kind.unlock_time().unwrap_or_default();
+            if let VestingKind::Block { unlock_block } = *kind {
+                // The horizon is a duration, it cannot be checked against a block number
+                if unlock_block == 0 {
+                    return Err(Error::InvalidUnlockTime);
+                }
+                if unlock_block <= self.env().block_number() {
+                    return Err(Error::UnlockInPast);
+                }
+            } else {
+                // Zero is what a caller who forgot to set the time would send
+                if unlock_time == 0 {
+                    return Err(Error::InvalidUnlockTime);
+                }
+                if unlock_time <= current_time {
+                    return Err(Error::UnlockInPast);
+                }
+                if let Some(max_unlock_horizon) = self.max_unlock_horizon {
+                    if unlock_time - current_time > max_unlock_horizon {
+                        return Err(Error::UnlockTooFar);
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        /// Stores a new schedule of `amount` owned by the caller, checked beforehand
+        /// with `ensure_valid_schedule`, and emits `Deposited`. Returns its ID.
+        fn record_schedule(
+            &mut self,
+            beneficiary: AccountId,
+            amount: Balance,
+            kind: VestingKind,
+            label: Option<Vec<u8>>,
+            revocable: bool,
+            pending_acceptance: bool
+        ) -> Result<u64> {
+            let owner = self.env().caller();
+            let unlock_time = kind.unlock_time().unwrap_or_default();
+
+            // Create new vesting schedule
+            let id = self.insert_schedule(VestingSchedule {
+                owner,
+                beneficiary,
+                amount,
+                released: 0,
+                kind,
+                label,
+                revocable,
+                beneficiary_locked: false,
+                pending_acceptance,
+            })?;
+
+            self.env().emit_event(Deposited {
+                beneficiary,
+                owner,
+                id,
+                amount,
+                unlock_time,
+            });
+
+            Ok(id)
+        }
+
+        /// Stores `schedule` under a new ID, indexes it and locks its funds.
+        /// Returns the new ID.
+        fn insert_schedule(&mut self, schedule: VestingSchedule) -> Result<u64> {
+            // Generate new schedule ID with overflow check
+            // Without this check, if id reaches 18,446,744,073,709,551,615 (u64::MAX)
+            // Adding 1 would wrap to 0 (integer overflow)
+            // IDs only ever grow and are never recycled, so the ID of a removed schedule
+            // can never point at a newer one in `schedules` or in a stale index entry
+            let id = self.id;
+            let next_id = id.checked_add(1).ok_or(Error::IdOverflow)?;
+
+            // Lock the funds before anything else is written, as it can fail on overflow
+            self.lock_funds(schedule.beneficiary, schedule.amount)?;
+            self.id = next_id;
+
+            // Store the schedule
+            self.store_schedule(id, &schedule);
+            self.active_ids.insert(self.schedule_count, &id);
+            self.active_positions.insert(id, &self.schedule_count);
+            self.schedule_count += 1;
+
+            // Update beneficiary's and owner's schedule lists
+            self.add_beneficiary_id(schedule.beneficiary, id);
+            self.add_owner_id(schedule.owner, id);
+
+            Ok(id)
+        }
+    }
+
+    //----------------------------------
+    // Testing Framework
+    //----------------------------------
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use ink::env::{
+            test::{
                 default_accounts,
                 set_caller,
+                set_callee,
+                set_account_balance,
                 set_value_transferred,
                 set_block_timestamp,
                 get_account_balance,
+                recorded_events,
+                advance_block,
             },
             DefaultEnvironment,
         };
 
-        /// Tests the scenario where the schedule ID counter overflows.
+        /// Tests the scenario where the schedule ID counter overflows.
+        ///
+        /// This test verifies that:
+        /// 1. When the `id` counter reaches its maximum value (u64::MAX).
+        /// 2. Attempting to create a new vesting schedule results in an `Error::IdOverflow`.
+        #[ink::test]
+        fn test_id_overflow() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let unlocktime = 242208000;
+            let mut vesting = Vesting::new();
+            ink::env::debug_println!("---- initial id: {}", vesting.id);
+
+            vesting.id = u64::MAX; // Set id to the maximum value
+            ink::env::debug_println!("---- maximum id: {}", vesting.id);
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_value_transferred::<DefaultEnvironment>(100);
+
+            // Act
+            let result = vesting.deposit_fund(accounts.bob, unlocktime);
+
+            // Assert
+            assert_eq!(result, Err(Error::IdOverflow));
+        }
+
+        /// Tests the scenario where a beneficiary attempts to withdraw funds before the unlock time.
+        ///
+        /// This test verifies that:
+        /// 1. Funds are deposited into a vesting schedule with a future unlock time.
+        /// 2. When the beneficiary attempts to withdraw funds before the unlock time.
+        /// 3. The `withdraw_fund` function returns `Err(Error::NoFundsAvailable)`.
+        #[ink::test]
+        fn test_withdraw_before_unlock() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let unlock_time_attempt: Timestamp = initial_time + 1000; // Unlock in the future
+            let amount: Balance = 100;
+
+            // Set the initial caller to Alice (the owner)
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<ink::env::DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            // Act
+            // Simulate a deposit of `amount` tokens from Alice to Bob, with a future unlock time
+            set_value_transferred::<ink::env::DefaultEnvironment>(amount);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time_attempt), Ok(0));
+            
+            // Set Bob as the caller (the beneficiary)
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            // Attempt to withdraw before unlock
+            let result = contract.withdraw_fund();
+
+            // Assert
+            // Verify that the withdrawal fails with the expected error
+            assert_eq!(result, Err(Error::NoFundsAvailable));
+        }
+
+        /// Tests the successful withdrawal of funds after the unlock period has passed with multiple vestings.
+        ///
+        /// This test verifies that:
+        /// 1. Multiple funds can be deposited into different vesting schedules for the same beneficiary.
+        /// 2. Each vesting can have different deposited amount and unlock time
+        /// 3. Funds cannot be withdrawn before the unlock time.
+        /// 4. All funds can be successfully withdrawn after the unlock time.
+        /// 5. The withdrawn amount is the sum of all deposited amounts.
+        #[ink::test]
+        fn tesst_successful_withdrawal_after_unlock() {
+            // Arrange
+            // Get test accounts
+            let accounts = default_accounts::<DefaultEnvironment>();
+            // Define initial timestamp
+            let initial_time: Timestamp = 242208000;
+            // Define unlock timestamps for each vesting
+            let unlock_time_1: Timestamp = 1820044800; //50 years later
+            let unlock_time_2: Timestamp = 1851580800; //51 years later
+            let unlock_time_3: Timestamp = 1883116800; //52 years later
+            // Define the amount of tokens to transfer for each vesting
+            let amount_1: Balance = 100;
+            let amount_2: Balance = 200;
+            let amount_3: Balance = 300;
+            // Calculate the total amount
+            let total_amount: Balance = amount_1 + amount_2 + amount_3;
+
+            // Set the initial caller to Alice (the owner)
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            // Set the initial block timestamp
+            set_block_timestamp::<ink::env::DefaultEnvironment>(initial_time);
+            // Instantiate the vesting contract
+            let mut contract = Vesting::new();
+
+            // Act
+            // Simulate multiple deposits from Alice to Bob, with different unlock times
+            set_value_transferred::<ink::env::DefaultEnvironment>(amount_1);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time_1), Ok(0));
+
+            set_value_transferred::<ink::env::DefaultEnvironment>(amount_2);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time_2), Ok(1));
+
+            set_value_transferred::<ink::env::DefaultEnvironment>(amount_3);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time_3), Ok(2));
+
+            // Advance the block timestamp to a time after all unlocks
+            set_block_timestamp::<ink::env::DefaultEnvironment>(unlock_time_3 + 1);
+
+            // Set the caller to Bob (the beneficiary)
+            set_caller::<DefaultEnvironment>(accounts.bob);
+
+            // Get Bob's initial balance
+            let initial_balance = get_account_balance::<DefaultEnvironment>(accounts.bob).expect(
+                "Failed to get initial balance"
+            );
+
+            // Attempt to withdraw the funds, which should now be unlocked
+            let result = contract.withdraw_fund();
+
+            // Get Bob's final balance
+            let final_balance = get_account_balance::<DefaultEnvironment>(accounts.bob).expect(
+                "Failed to get final balance"
+            );
+
+            // Assert
+            // Check if the withdrawn amount is equal to the total amount
+            assert_eq!(result, Ok(total_amount));
+            // Check if the difference between the final and initial balance is equal to the total amount
+            assert_eq!(final_balance - initial_balance, total_amount);
+        }
+
+        /// Tests the release of a linearly vesting schedule over time.
+        ///
+        /// This test verifies that:
+        /// 1. Nothing can be withdrawn before the vesting starts.
+        /// 2. Halfway through the duration, half of the amount can be withdrawn.
+        /// 3. Only the newly vested delta is transferred on subsequent withdrawals.
+        /// 4. The schedule is removed once the full amount has been released.
+        #[ink::test]
+        fn test_linear_vesting_release() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let start_time: Timestamp = 242208000;
+            let duration: Timestamp = 1000;
+            let amount: Balance = 100;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(start_time - 1);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(amount);
+            assert_eq!(contract.deposit_fund_linear(accounts.bob, start_time, duration), Ok(0));
+
+            // Act & Assert
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            // Before the start nothing is vested
+            assert_eq!(contract.withdraw_fund(), Err(Error::NoFundsAvailable));
+
+            // Halfway through the duration half of the amount is vested
+            let initial_balance = get_account_balance::<DefaultEnvironment>(accounts.bob).expect(
+                "Failed to get initial balance"
+            );
+            set_block_timestamp::<DefaultEnvironment>(start_time + duration / 2);
+            assert_eq!(contract.withdraw_fund(), Ok(50));
+            let mid_balance = get_account_balance::<DefaultEnvironment>(accounts.bob).expect(
+                "Failed to get mid balance"
+            );
+            assert_eq!(mid_balance - initial_balance, 50);
+
+            // Nothing new vested at the same timestamp
+            assert_eq!(contract.withdraw_fund(), Err(Error::NoFundsAvailable));
+            assert_eq!(contract.schedules.get(0).map(|s| s.released), Some(50));
+
+            // Past the end the remainder is released and the schedule removed
+            set_block_timestamp::<DefaultEnvironment>(start_time + duration + 1);
+            assert_eq!(contract.withdraw_fund(), Ok(50));
+            let final_balance = get_account_balance::<DefaultEnvironment>(accounts.bob).expect(
+                "Failed to get final balance"
+            );
+            assert_eq!(final_balance - initial_balance, amount);
+            assert!(contract.schedules.get(0).is_none());
+            assert_eq!(contract.beneficiary_to_ids.get(accounts.bob), None);
+            contract.assert_tvl_invariant();
+        }
+
+        /// Tests the linear vesting math against rounding and overflow.
+        ///
+        /// This test verifies that:
+        /// 1. The vested amount is rounded down.
+        /// 2. Amounts close to `u128::MAX` do not overflow the intermediate product.
+        /// 3. A zero duration behaves like a cliff at `start_time`.
+        /// 4. A TGE amount above the schedule amount does not underflow.
+        #[ink::test]
+        fn test_linear_vested_amount() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut schedule = VestingSchedule {
+                owner: accounts.alice,
+                beneficiary: accounts.bob,
+                amount: 10,
+                released: 0,
+                kind: VestingKind::Linear { start_time: 100, duration: 3 },
+                label: None,
+                revocable: true,
+                beneficiary_locked: false,
+                pending_acceptance: false,
+            };
+
+            // Act & Assert
+            assert_eq!(schedule.vested_amount(101, 0), 3);
+            assert_eq!(schedule.vested_amount(102, 0), 6);
+            assert_eq!(schedule.vested_amount(103, 0), 10);
+
+            // u128::MAX * 3 would overflow, the exact result is floor(u128::MAX * 3 / 4)
+            schedule.amount = u128::MAX;
+            schedule.kind = VestingKind::Linear { start_time: 0, duration: 4 };
+            assert_eq!(schedule.vested_amount(3, 0), (u128::MAX / 4) * 3 + 2);
+
+            schedule.amount = 10;
+            schedule.kind = VestingKind::Linear { start_time: 100, duration: 0 };
+            assert_eq!(schedule.vested_amount(99, 0), 0);
+            assert_eq!(schedule.vested_amount(100, 0), 10);
+
+            schedule.kind = VestingKind::LinearWithTge { tge_amount: 20, start_time: 100, duration: 10 };
+            assert_eq!(schedule.vested_amount(99, 0), 10);
+            assert_eq!(schedule.vested_amount(110, 0), 10);
+        }
+
+        /// Tests the claimable amount of each schedule kind around its boundaries.
+        ///
+        /// This test verifies that:
+        /// 1. A cliff schedule is claimable in full exactly at its unlock time.
+        /// 2. A linear schedule is claimable pro rata, minus what was already released.
+        /// 3. A tranche schedule is claimable tranche by tranche, at each tranche time.
+        /// 4. A block schedule is claimable in full exactly at its unlock block, whatever the time.
+        #[ink::test]
+        fn test_schedule_claimable() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let mut schedule = VestingSchedule {
+                owner: accounts.alice,
+                beneficiary: accounts.bob,
+                amount: 100,
+                released: 0,
+                kind: VestingKind::Cliff { unlock_time: 1000 },
+                label: None,
+                revocable: true,
+                beneficiary_locked: false,
+                pending_acceptance: false,
+            };
+
+            // Act & Assert
+            assert_eq!(schedule.claimable(0, 0), 0);
+            assert_eq!(schedule.claimable(999, 0), 0);
+            assert_eq!(schedule.claimable(1000, 0), 100);
+            assert_eq!(schedule.claimable(u64::MAX, 0), 100);
+
+            schedule.kind = VestingKind::Linear { start_time: 1000, duration: 1000 };
+            assert_eq!(schedule.claimable(1000, 0), 0);
+            assert_eq!(schedule.claimable(1250, 0), 25);
+            schedule.released = 25;
+            assert_eq!(schedule.claimable(1250, 0), 0);
+            assert_eq!(schedule.claimable(1999, 0), 74);
+            assert_eq!(schedule.claimable(2000, 0), 75);
+
+            schedule.released = 0;
+            schedule.kind = VestingKind::Tranches { tranches: vec![(1000, 30), (2000, 70)] };
+            assert_eq!(schedule.claimable(999, 0), 0);
+            assert_eq!(schedule.claimable(1000, 0), 30);
+            assert_eq!(schedule.claimable(1999, 0), 30);
+            assert_eq!(schedule.claimable(2000, 0), 100);
+            schedule.released = 30;
+            schedule.kind = VestingKind::Tranches { tranches: vec![(2000, 70)] };
+            assert_eq!(schedule.claimable(1999, 0), 0);
+            assert_eq!(schedule.claimable(2000, 0), 70);
+
+            schedule.released = 0;
+            schedule.kind = VestingKind::Block { unlock_block: 10 };
+            assert_eq!(schedule.claimable(u64::MAX, 9), 0);
+            assert_eq!(schedule.claimable(0, 10), 100);
+            assert_eq!(schedule.claimable(0, 11), 100);
+        }
+
+        /// Tests that a deposit emits a `Deposited` event.
+        ///
+        /// This test verifies that:
+        /// 1. Exactly one event is recorded for a deposit.
+        /// 2. The event carries the beneficiary, owner, ID, amount and unlock time of the schedule.
+        #[ink::test]
+        fn test_deposit_emits_event() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let unlock_time: Timestamp = 242208000;
+            let amount: Balance = 100;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            let mut contract = Vesting::new();
+
+            // Act
+            set_value_transferred::<DefaultEnvironment>(amount);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(0));
+
+            // Assert
+            let events: Vec<_> = recorded_events().collect();
+            assert_eq!(events.len(), 1);
+            let event = <Deposited as scale::Decode>::decode(&mut &events[0].data[..]).expect(
+                "Failed to decode Deposited event"
+            );
+            assert_eq!(event.beneficiary, accounts.bob);
+            assert_eq!(event.owner, accounts.alice);
+            assert_eq!(event.id, 0);
+            assert_eq!(event.amount, amount);
+            assert_eq!(event.unlock_time, unlock_time);
+        }
+
+        /// Tests that a withdrawal emits a `Withdrawn` event.
+        ///
+        /// This test verifies that:
+        /// 1. The event carries the beneficiary and the total amount transferred.
+        /// 2. Only the IDs of the schedules that released funds are listed.
+        #[ink::test]
+        fn test_withdraw_emits_event() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 10), Ok(0));
+            set_value_transferred::<DefaultEnvironment>(200);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(1));
+            set_value_transferred::<DefaultEnvironment>(300);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 20), Ok(2));
+
+            // Act
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 100);
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.withdraw_fund(), Ok(400));
+
+            // Assert
+            let events: Vec<_> = recorded_events().collect();
+            assert_eq!(events.len(), 6);
+            let event = <Withdrawn as scale::Decode>::decode(&mut &events[5].data[..]).expect(
+                "Failed to decode Withdrawn event"
+            );
+            assert_eq!(event.beneficiary, accounts.bob);
+            assert_eq!(event.total_amount, 400);
+            assert_eq!(event.schedule_ids, vec![0, 2]);
+        }
+
+        /// Tests that an owner can revoke a schedule before it vests.
+        ///
+        /// This test verifies that:
+        /// 1. A non-owner cannot revoke the schedule.
+        /// 2. The owner gets the full amount refunded.
+        /// 3. The revoked schedule no longer appears for the beneficiary.
+        #[ink::test]
+        fn test_revoke_before_unlock() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let amount: Balance = 100;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(amount);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(0));
+
+            // Act & Assert
+            // Neither the beneficiary nor a stranger can revoke
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.revoke(0), Err(Error::NotOwner));
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.revoke(0), Err(Error::NotOwner));
+
+            // The owner reclaims the funds
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            let initial_balance = get_account_balance::<DefaultEnvironment>(accounts.alice).expect(
+                "Failed to get initial balance"
+            );
+            assert_eq!(contract.revoke(0), Ok(()));
+            let final_balance = get_account_balance::<DefaultEnvironment>(accounts.alice).expect(
+                "Failed to get final balance"
+            );
+            assert_eq!(final_balance - initial_balance, amount);
+
+            // The schedule is gone for the beneficiary
+            assert!(contract.schedules.get(0).is_none());
+            assert_eq!(contract.beneficiary_to_ids.get(accounts.bob), None);
+            assert_eq!(contract.revoke(0), Err(Error::ScheduleNotFound));
+            contract.assert_tvl_invariant();
+        }
+
+        /// Tests that a schedule cannot be revoked once it has vested.
+        ///
+        /// This test verifies that:
+        /// 1. A cliff schedule cannot be revoked at or after its unlock time.
+        /// 2. A linear schedule cannot be revoked once it has started vesting.
+        #[ink::test]
+        fn test_revoke_after_unlock() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(0));
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund_linear(accounts.bob, initial_time, 10_000), Ok(1));
+
+            // Act & Assert
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 1000);
+            assert_eq!(contract.revoke(0), Err(Error::AlreadyVested));
+            assert_eq!(contract.revoke(1), Err(Error::AlreadyVested));
+            assert_eq!(contract.beneficiary_to_ids.get(accounts.bob), Some(vec![0, 1]));
+        }
+
+        /// Tests listing the schedules of a beneficiary.
+        ///
+        /// This test verifies that:
+        /// 1. A beneficiary without schedules gets an empty list.
+        /// 2. Each schedule is returned with its ID, owner, amount and unlock time.
+        /// 3. The claimable amount reflects the current block time.
+        #[ink::test]
+        fn test_get_schedules() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(0));
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            set_value_transferred::<DefaultEnvironment>(200);
+            assert_eq!(contract.deposit_fund_linear(accounts.bob, initial_time, 1000), Ok(1));
+
+            // Act
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 500);
+            let schedules = contract.get_schedules(accounts.bob);
+
+            // Assert
+            assert!(contract.get_schedules(accounts.alice).is_empty());
+            assert_eq!(schedules, vec![
+                (0, VestingScheduleInfo {
+                    owner: accounts.alice,
+                    beneficiary: accounts.bob,
+                    amount: 100,
+                    released: 0,
+                    unlock_time: initial_time + 1000,
+                    unlock_block: None,
+                    claimable: 0,
+                    label: None,
+                    revocable: true,
+                    pending_acceptance: false,
+                }),
+                (1, VestingScheduleInfo {
+                    owner: accounts.charlie,
+                    beneficiary: accounts.bob,
+                    amount: 200,
+                    released: 0,
+                    unlock_time: initial_time + 1000,
+                    unlock_block: None,
+                    claimable: 100,
+                    label: None,
+                    revocable: true,
+                    pending_acceptance: false,
+                })
+            ]);
+        }
+
+        /// Tests the released amount reported by the schedule getter.
+        ///
+        /// This test verifies that:
+        /// 1. A new schedule reports nothing released.
+        /// 2. Partial and per-schedule withdrawals add up in `released`.
+        /// 3. A fully released schedule is removed.
+        #[ink::test]
+        fn test_schedule_info_released() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let start_time: Timestamp = initial_time + 1000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund_linear(accounts.bob, start_time, 1000), Ok(0));
+            assert_eq!(contract.get_schedule(0).map(|s| s.released), Some(0));
+
+            // Act & Assert
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            set_block_timestamp::<DefaultEnvironment>(start_time + 700);
+            assert_eq!(contract.withdraw_partial(30), Ok(30));
+            assert_eq!(contract.get_schedule(0).map(|s| s.released), Some(30));
+
+            assert_eq!(contract.withdraw_schedule(0), Ok(40));
+            let info = contract.get_schedule(0).expect("Schedule should still exist");
+            assert_eq!((info.released, info.amount), (70, 100));
+
+            set_block_timestamp::<DefaultEnvironment>(start_time + 1000);
+            assert_eq!(contract.withdraw_fund(), Ok(30));
+            assert_eq!(contract.get_schedule(0), None);
+        }
+
+        /// Tests that a withdrawal drops index entries without a schedule.
+        ///
+        /// This test verifies that:
+        /// 1. A stale ID in the beneficiary index does not make the withdrawal fail.
+        /// 2. The stale ID is removed from the index while locked schedules are kept.
+        #[ink::test]
+        fn test_withdraw_fund_drops_stale_ids() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(0));
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 2000), Ok(1));
+
+            // Seed an index entry pointing at a schedule that does not exist
+            contract.beneficiary_to_ids.insert(accounts.bob, &vec![0, 7, 1]);
+
+            // Act
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 1000);
+            let result = contract.withdraw_fund();
+
+            // Assert
+            assert_eq!(result, Ok(100));
+            assert_eq!(contract.beneficiary_to_ids.get(accounts.bob), Some(vec![1]));
+            contract.assert_tvl_invariant();
+        }
+
+        /// Tests withdrawing a single schedule by ID.
+        ///
+        /// This test verifies that:
+        /// 1. Only the beneficiary can withdraw the schedule.
+        /// 2. A locked schedule cannot be withdrawn.
+        /// 3. Withdrawing an unlocked schedule returns and transfers only its amount.
+        /// 4. Other schedules of the beneficiary are left untouched.
+        #[ink::test]
+        fn test_withdraw_schedule() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 10), Ok(0));
+            set_value_transferred::<DefaultEnvironment>(200);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 20), Ok(1));
+            set_value_transferred::<DefaultEnvironment>(300);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(2));
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 100);
+
+            // Act & Assert
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.withdraw_schedule(1), Err(Error::NotBeneficiary));
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.withdraw_schedule(2), Err(Error::NotYetUnlocked));
+            assert_eq!(contract.withdraw_schedule(7), Err(Error::ScheduleNotFound));
+
+            let initial_balance = get_account_balance::<DefaultEnvironment>(accounts.bob).expect(
+                "Failed to get initial balance"
+            );
+            assert_eq!(contract.withdraw_schedule(1), Ok(200));
+            let final_balance = get_account_balance::<DefaultEnvironment>(accounts.bob).expect(
+                "Failed to get final balance"
+            );
+            assert_eq!(final_balance - initial_balance, 200);
+
+            assert!(contract.schedules.get(1).is_none());
+            assert_eq!(contract.beneficiary_to_ids.get(accounts.bob), Some(vec![0, 2]));
+            assert_eq!(contract.withdraw_schedule(1), Err(Error::ScheduleNotFound));
+            contract.assert_tvl_invariant();
+        }
+
+        /// Tests a full deposit and withdrawal cycle against a mocked PSP22 token.
+        ///
+        /// This test verifies that:
+        /// 1. Depositing pulls the tokens from the owner into the contract.
+        /// 2. Withdrawing transfers the vested tokens to the beneficiary.
+        /// 3. A failing token call is reported as `Error::TokenCallFailed`.
+        /// 4. An invalid deposit fails before any tokens are pulled.
+        #[ink::test]
+        fn test_psp22_deposit_and_withdraw() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let token = accounts.frank;
+            let initial_time: Timestamp = 242208000;
+            let unlock_time: Timestamp = initial_time + 1000;
+            let amount: Balance = 100;
+
+            let contract_id = accounts.django;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_callee::<DefaultEnvironment>(contract_id);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new_psp22(token);
+            mock_token::mint(accounts.alice, 1000);
+
+            // Act & Assert
+            // Deposit moves the tokens into the contract
+            assert_eq!(contract.deposit_fund_token(accounts.bob, unlock_time, amount), Ok(0));
+            assert_eq!(mock_token::balance_of(accounts.alice), 900);
+            assert_eq!(mock_token::balance_of(contract_id), amount);
+
+            // Depositing more than the owner holds fails
+            assert_eq!(
+                contract.deposit_fund_token(accounts.bob, unlock_time, 1000),
+                Err(Error::TokenCallFailed)
+            );
+
+            // Invalid deposits fail before calling the token
+            assert_eq!(
+                contract.deposit_fund_token(accounts.bob, initial_time, amount),
+                Err(Error::UnlockInPast)
+            );
+            assert_eq!(contract.deposit_fund_token(accounts.bob, unlock_time, 0), Err(Error::ZeroAmount));
+            assert_eq!(mock_token::balance_of(accounts.alice), 900);
+
+            // Withdrawal after unlock moves the tokens to the beneficiary
+            set_block_timestamp::<DefaultEnvironment>(unlock_time);
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.withdraw_fund(), Ok(100));
+            assert_eq!(mock_token::balance_of(accounts.bob), amount);
+            assert_eq!(mock_token::balance_of(contract_id), 0);
+            contract.assert_tvl_invariant();
+        }
+
+        /// Tests that withdrawals in token mode never touch the native balance.
+        ///
+        /// This test verifies that:
+        /// 1. `withdraw_fund` and `withdraw_schedule` pay out through the token.
+        /// 2. The native balances of the contract and the beneficiary are unchanged.
+        #[ink::test]
+        fn test_psp22_withdraw_skips_native_transfer() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let token = accounts.frank;
+            let initial_time: Timestamp = 242208000;
+            let unlock_time: Timestamp = initial_time + 1000;
+            let contract_id = accounts.django;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_callee::<DefaultEnvironment>(contract_id);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new_psp22(token);
+            mock_token::mint(accounts.alice, 300);
+            assert_eq!(contract.deposit_fund_token(accounts.bob, unlock_time, 100), Ok(0));
+            assert_eq!(contract.deposit_fund_token(accounts.bob, unlock_time, 200), Ok(1));
+
+            // Native value the contract holds that a native transfer would move
+            set_account_balance::<DefaultEnvironment>(contract_id, 1_000_000);
+            let bob_native = get_account_balance::<DefaultEnvironment>(accounts.bob).expect(
+                "Failed to get initial balance"
+            );
+
+            // Act
+            set_block_timestamp::<DefaultEnvironment>(unlock_time);
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.withdraw_schedule(0), Ok(100));
+            assert_eq!(contract.withdraw_fund(), Ok(200));
+
+            // Assert
+            assert_eq!(mock_token::balance_of(accounts.bob), 300);
+            assert_eq!(mock_token::balance_of(contract_id), 0);
+            assert_eq!(get_account_balance::<DefaultEnvironment>(contract_id), Ok(1_000_000));
+            assert_eq!(get_account_balance::<DefaultEnvironment>(accounts.bob), Ok(bob_native));
+            contract.assert_tvl_invariant();
+        }
+
+        /// Tests that token deposits are rejected when no token is configured.
+        #[ink::test]
+        fn test_deposit_fund_token_without_token() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            let mut contract = Vesting::new();
+
+            // Act & Assert
+            assert_eq!(
+                contract.deposit_fund_token(accounts.bob, 242208000, 100),
+                Err(Error::WrongMode)
+            );
+        }
+
+        /// Tests the release of a cliff-then-linear schedule.
+        ///
+        /// This test verifies that:
+        /// 1. A cliff that is not before the end time is rejected.
+        /// 2. Nothing is claimable before or exactly at the cliff.
+        /// 3. Funds vest proportionally between the cliff and the end time.
+        /// 4. The full amount is claimable at and after the end time.
+        #[ink::test]
+        fn test_cliff_linear_vesting() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let cliff_time: Timestamp = initial_time + 1000;
+            let end_time: Timestamp = cliff_time + 1000;
+            let amount: Balance = 100;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(amount);
+            assert_eq!(
+                contract.deposit_fund_cliff_linear(accounts.bob, end_time, cliff_time),
+                Err(Error::InvalidSchedule)
+            );
+            assert_eq!(
+                contract.deposit_fund_cliff_linear(accounts.bob, cliff_time, cliff_time),
+                Err(Error::InvalidSchedule)
+            );
+            assert_eq!(contract.deposit_fund_cliff_linear(accounts.bob, cliff_time, end_time), Ok(0));
+
+            // Act & Assert
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            set_block_timestamp::<DefaultEnvironment>(cliff_time - 1);
+            assert_eq!(contract.withdraw_fund(), Err(Error::NoFundsAvailable));
+            set_block_timestamp::<DefaultEnvironment>(cliff_time);
+            assert_eq!(contract.withdraw_fund(), Err(Error::NoFundsAvailable));
+
+            // Mid-way between the cliff and the end, half is claimable
+            set_block_timestamp::<DefaultEnvironment>(cliff_time + 500);
+            assert_eq!(contract.withdraw_schedule(0), Ok(50));
+
+            // Past the end, the remainder is claimable
+            set_block_timestamp::<DefaultEnvironment>(end_time + 1);
+            assert_eq!(contract.withdraw_schedule(0), Ok(50));
+            assert!(contract.schedules.get(0).is_none());
+            contract.assert_tvl_invariant();
+        }
+
+        /// Tests the release of a schedule with an immediate TGE portion.
+        ///
+        /// This test verifies that:
+        /// 1. A TGE share above 100% is rejected.
+        /// 2. The TGE portion is claimable right after the deposit.
+        /// 3. The remainder follows the linear curve from the start time.
+        /// 4. The full amount is released at the end.
+        #[ink::test]
+        fn test_tge_vesting() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let start_time: Timestamp = initial_time + 1000;
+            let duration: Timestamp = 1000;
+            let amount: Balance = 1000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(amount);
+            assert_eq!(
+                contract.deposit_fund_tge(accounts.bob, 10_001, start_time, duration),
+                Err(Error::InvalidSchedule)
+            );
+            // 20% at TGE, the other 800 vest over the duration
+            assert_eq!(contract.deposit_fund_tge(accounts.bob, 2000, start_time, duration), Ok(0));
+
+            // Act & Assert
+            assert_eq!(contract.get_schedule(0).map(|s| s.claimable), Some(200));
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.withdraw_schedule(0), Ok(200));
+            set_block_timestamp::<DefaultEnvironment>(start_time);
+            assert_eq!(contract.withdraw_fund(), Err(Error::NoFundsAvailable));
+
+            // A quarter of the way through, a quarter of the remainder is claimable
+            set_block_timestamp::<DefaultEnvironment>(start_time + 250);
+            assert_eq!(contract.withdraw_schedule(0), Ok(200));
+
+            set_block_timestamp::<DefaultEnvironment>(start_time + duration);
+            assert_eq!(contract.withdraw_schedule(0), Ok(600));
+            assert!(contract.schedules.get(0).is_none());
+            contract.assert_tvl_invariant();
+        }
+
+        /// Tests draining many schedules with bounded withdrawals.
+        ///
+        /// This test verifies that:
+        /// 1. Each call processes at most `max` schedules.
+        /// 2. Locked schedules are kept and rotated so later calls reach the matured ones.
+        /// 3. Repeated calls eventually drain all unlocked funds and leave only locked schedules.
+        #[ink::test]
+        fn test_withdraw_fund_limited() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let schedule_count: u64 = 500;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            // The first 100 schedules stay locked, the other 400 mature
+            for i in 0..schedule_count {
+                let unlock_time = if i < 100 { initial_time + 1000 } else { initial_time + 10 };
+                set_value_transferred::<DefaultEnvironment>(1);
+                assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(i));
+            }
+
+            // Act
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 100);
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            let mut withdrawn: Balance = 0;
+            let mut calls = 0;
+            while withdrawn < 400 {
+                let amount = contract.withdraw_fund_limited(50).expect("Withdrawal failed");
+                assert!(amount <= 50);
+                withdrawn += amount;
+                calls += 1;
+                assert!(calls <= 10, "Limited withdrawals did not make progress");
+            }
+
+            // Assert
+            assert_eq!(withdrawn, 400);
+            assert_eq!(calls, 10);
+            assert_eq!(contract.beneficiary_to_ids.get(accounts.bob).map(|ids| ids.len()), Some(100));
+            assert_eq!(contract.withdraw_fund(), Err(Error::NoFundsAvailable));
+            contract.assert_tvl_invariant();
+        }
+
+        /// Tests the pull-payment fallback when a withdrawal transfer fails.
+        ///
+        /// This test verifies that:
+        /// 1. A failing transfer does not make the withdrawal fail.
+        /// 2. The released amount is credited to the beneficiary instead.
+        /// 3. The failure is observable on-chain through a `Credited` event.
+        /// 4. The credit can later be pulled with `claim_credit`.
+        #[ink::test]
+        fn test_failed_transfer_is_credited() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            // A fresh contract account without any balance cannot cover transfers
+            let contract_id = AccountId::from([0x42; 32]);
+            let initial_time: Timestamp = 242208000;
+            let amount: Balance = 100;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_callee::<DefaultEnvironment>(contract_id);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(amount);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 10), Ok(0));
+
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 100);
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            let initial_balance = get_account_balance::<DefaultEnvironment>(accounts.bob).expect(
+                "Failed to get initial balance"
+            );
+
+            // Act & Assert
+            assert_eq!(contract.withdraw_fund(), Ok(100));
+            assert_eq!(contract.credited_balances.get(accounts.bob), Some(amount));
+            assert!(contract.schedules.get(0).is_none());
+
+            let events: Vec<_> = recorded_events().collect();
+            let credited = <Credited as scale::Decode>::decode(
+                &mut &events[events.len() - 1].data[..]
+            ).expect("Failed to decode Credited event");
+            assert_eq!(credited.beneficiary, accounts.bob);
+            assert_eq!(credited.amount, amount);
+
+            // Once the contract can pay, the credit is transferred
+            set_account_balance::<DefaultEnvironment>(contract_id, 1_000_000);
+            assert_eq!(contract.claim_credit(), Ok(amount));
+            let final_balance = get_account_balance::<DefaultEnvironment>(accounts.bob).expect(
+                "Failed to get final balance"
+            );
+            assert_eq!(final_balance - initial_balance, amount);
+            assert_eq!(contract.credited_balances.get(accounts.bob), None);
+            assert_eq!(contract.claim_credit(), Err(Error::NoFundsAvailable));
+        }
+
+        /// Tests redirecting a schedule to a new beneficiary.
+        ///
+        /// This test verifies that:
+        /// 1. A non-owner cannot reassign the schedule.
+        /// 2. The schedule moves from the old beneficiary's list to the new one's.
+        /// 3. The new beneficiary can withdraw the funds after unlock.
+        /// 4. An unlocked schedule can no longer be reassigned.
+        #[ink::test]
+        fn test_reassign_beneficiary() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let unlock_time: Timestamp = initial_time + 1000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(0));
+            set_value_transferred::<DefaultEnvironment>(200);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(1));
+
+            // Act & Assert
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.reassign_beneficiary(0, accounts.bob), Err(Error::NotOwner));
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.reassign_beneficiary(0, accounts.charlie), Ok(()));
+            assert_eq!(contract.beneficiary_to_ids.get(accounts.bob), Some(vec![1]));
+            assert_eq!(contract.beneficiary_to_ids.get(accounts.charlie), Some(vec![0]));
+
+            // Once unlocked the schedule stays with its beneficiary
+            set_block_timestamp::<DefaultEnvironment>(unlock_time);
+            assert_eq!(
+                contract.reassign_beneficiary(1, accounts.charlie),
+                Err(Error::AlreadyVested)
+            );
+
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.withdraw_schedule(0), Ok(100));
+            contract.assert_tvl_invariant();
+        }
+
+        /// Tests the running totals of locked funds.
+        ///
+        /// This test verifies that:
+        /// 1. Deposits increase the total and per-beneficiary locked funds.
+        /// 2. Partial and full withdrawals decrease them by the amount released.
+        /// 3. Revocations decrease them by the revoked amount.
+        #[ink::test]
+        fn test_locked_totals() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            // Act & Assert
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund_linear(accounts.bob, initial_time, 1000), Ok(0));
+            set_value_transferred::<DefaultEnvironment>(200);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(1));
+            set_value_transferred::<DefaultEnvironment>(300);
+            assert_eq!(contract.deposit_fund(accounts.charlie, initial_time + 1000), Ok(2));
+            assert_eq!(contract.total_locked(), 600);
+            assert_eq!(contract.locked_of(accounts.bob), 300);
+            assert_eq!(contract.locked_of(accounts.charlie), 300);
+            assert_eq!(contract.locked_of(accounts.django), 0);
+
+            // Partial withdrawal of the linear schedule
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 500);
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.withdraw_fund(), Ok(50));
+            assert_eq!(contract.total_locked(), 550);
+            assert_eq!(contract.locked_of(accounts.bob), 250);
+
+            // Revoking charlie's schedule
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.revoke(2), Ok(()));
+            assert_eq!(contract.total_locked(), 250);
+            assert_eq!(contract.locked_of(accounts.charlie), 0);
+
+            // Full withdrawal
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 1000);
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.withdraw_fund(), Ok(250));
+            assert_eq!(contract.total_locked(), 0);
+            assert_eq!(contract.locked_of(accounts.bob), 0);
+        }
+
+        /// Tests the emergency pause.
+        ///
+        /// This test verifies that:
+        /// 1. Only the admin can pause and unpause the contract.
+        /// 2. Deposits and withdrawals are blocked while paused.
+        /// 3. Operations resume after unpausing.
+        #[ink::test]
+        fn test_pause() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 10), Ok(0));
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 100);
+
+            // Act & Assert
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.pause(), Err(Error::NotAdmin));
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.pause(), Ok(()));
+            assert!(contract.is_paused());
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Err(Error::Paused));
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.withdraw_fund(), Err(Error::Paused));
+            assert_eq!(contract.withdraw_schedule(0), Err(Error::Paused));
+            assert_eq!(contract.unpause(), Err(Error::NotAdmin));
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.unpause(), Ok(()));
+            assert!(!contract.is_paused());
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.withdraw_fund(), Ok(100));
+        }
+
+        /// Tests topping up an existing schedule.
+        ///
+        /// This test verifies that:
+        /// 1. Only the owner can top up a schedule, and not with zero value.
+        /// 2. Top-ups add to the schedule amount without creating new schedules.
+        /// 3. The total withdrawn equals the sum of the deposit and the top-ups.
+        /// 4. A schedule cannot be topped up once unlocked.
+        #[ink::test]
+        fn test_top_up() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let unlock_time: Timestamp = initial_time + 1000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(0));
+
+            // Act & Assert
+            set_value_transferred::<DefaultEnvironment>(0);
+            assert_eq!(contract.top_up(0), Err(Error::ZeroAmount));
+            set_value_transferred::<DefaultEnvironment>(200);
+            assert_eq!(contract.top_up(0), Ok(()));
+            set_value_transferred::<DefaultEnvironment>(300);
+            assert_eq!(contract.top_up(0), Ok(()));
+            assert_eq!(contract.top_up(1), Err(Error::ScheduleNotFound));
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.top_up(0), Err(Error::NotOwner));
+
+            assert_eq!(contract.beneficiary_to_ids.get(accounts.bob), Some(vec![0]));
+            assert_eq!(contract.locked_of(accounts.bob), 600);
+
+            set_block_timestamp::<DefaultEnvironment>(unlock_time);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.top_up(0), Err(Error::AlreadyVested));
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.withdraw_schedule(0), Ok(600));
+            contract.assert_tvl_invariant();
+        }
+
+        /// Tests counting and paging through the schedule IDs of a beneficiary.
+        ///
+        /// This test verifies that:
+        /// 1. A beneficiary without schedules has a count of zero and no IDs.
+        /// 2. Pages return the requested slice of the ID list.
+        /// 3. A `start` past the end of the list returns no IDs.
+        #[ink::test]
+        fn test_schedule_count_and_pages() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let unlock_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            let mut contract = Vesting::new();
+
+            for i in 0..5 {
+                set_value_transferred::<DefaultEnvironment>(100);
+                assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(i));
+            }
+
+            // Act & Assert
+            assert_eq!(contract.schedule_count(accounts.charlie), 0);
+            assert!(contract.get_schedule_ids_paged(accounts.charlie, 0, 10).is_empty());
+
+            assert_eq!(contract.schedule_count(accounts.bob), 5);
+            assert_eq!(contract.get_schedule_ids_paged(accounts.bob, 0, 10), vec![0, 1, 2, 3, 4]);
+            assert_eq!(contract.get_schedule_ids_paged(accounts.bob, 1, 2), vec![1, 2]);
+            assert_eq!(contract.get_schedule_ids_paged(accounts.bob, 4, 2), vec![4]);
+            assert!(contract.get_schedule_ids_paged(accounts.bob, 5, 2).is_empty());
+            assert!(contract.get_schedule_ids_paged(accounts.bob, u32::MAX, 2).is_empty());
+        }
+
+        /// Tests the reentrancy guard around withdrawals.
+        ///
+        /// This test verifies that:
+        /// 1. A withdrawal attempted while another one is in progress is rejected.
+        /// 2. The lock is released after successful and failed withdrawals.
+        #[ink::test]
+        fn test_reentrancy_guard() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 10), Ok(0));
+            set_value_transferred::<DefaultEnvironment>(200);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 20), Ok(1));
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 100);
+            set_caller::<DefaultEnvironment>(accounts.bob);
+
+            // Act & Assert
+            // Simulate a re-entrant call made while a withdrawal holds the lock
+            let nested = contract.non_reentrant(|this| this.withdraw_fund());
+            assert_eq!(nested, Err(Error::Reentrancy));
+            let nested = contract.non_reentrant(|this| this.withdraw_schedule(0));
+            assert_eq!(nested, Err(Error::Reentrancy));
+            assert_eq!(contract.reentrancy_lock.get(), Some(false));
+
+            // The lock is released on error paths
+            assert_eq!(contract.withdraw_schedule(7), Err(Error::ScheduleNotFound));
+            assert_eq!(contract.claim_credit(), Err(Error::NoFundsAvailable));
+            assert_eq!(contract.reentrancy_lock.get(), Some(false));
+
+            // And on success
+            assert_eq!(contract.withdraw_schedule(0), Ok(100));
+            assert_eq!(contract.withdraw_fund(), Ok(200));
+            assert_eq!(contract.reentrancy_lock.get(), Some(false));
+        }
+
+        /// Tests the validation of the unlock time on deposit.
+        ///
+        /// This test verifies that:
+        /// 1. Unlock times in the past or at the current block time are rejected.
+        /// 2. Unlock times beyond the configured horizon are rejected.
+        /// 3. Unlock times up to the horizon are accepted.
+        #[ink::test]
+        fn test_unlock_time_validation() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let max_horizon: Timestamp = 1000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new_with_max_horizon(max_horizon);
+            set_value_transferred::<DefaultEnvironment>(100);
+
+            // Act & Assert
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time - 1), Err(Error::UnlockInPast));
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time), Err(Error::UnlockInPast));
+            assert_eq!(
+                contract.deposit_fund(accounts.bob, initial_time + max_horizon + 1),
+                Err(Error::UnlockTooFar)
+            );
+            assert_eq!(
+                contract.deposit_fund_linear(accounts.bob, initial_time, max_horizon + 1),
+                Err(Error::UnlockTooFar)
+            );
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1), Ok(0));
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + max_horizon), Ok(1));
+
+            // Without a horizon any future unlock time is accepted
+            let mut contract = Vesting::new();
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time), Err(Error::UnlockInPast));
+            assert_eq!(contract.deposit_fund(accounts.bob, u64::MAX), Ok(0));
+        }
+
+        /// Tests revoking only the unvested part of linear schedules.
+        ///
+        /// This test verifies that:
+        /// 1. At 0% vested, the full amount is refunded and the schedule removed.
+        /// 2. At 50% vested, half is refunded and the other half is claimable right away.
+        /// 3. At 100% vested, nothing can be revoked.
+        #[ink::test]
+        fn test_revoke_partial() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let start_time: Timestamp = initial_time + 1000;
+            let duration: Timestamp = 1000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            for i in 0..3 {
+                set_value_transferred::<DefaultEnvironment>(100);
+                assert_eq!(contract.deposit_fund_linear(accounts.bob, start_time, duration), Ok(i));
+            }
+
+            // Act & Assert
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.revoke_partial(0), Err(Error::NotOwner));
+
+            // 0% vested
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.revoke_partial(0), Ok(100));
+            assert!(contract.schedules.get(0).is_none());
+            assert_eq!(contract.beneficiary_to_ids.get(accounts.bob), Some(vec![1, 2]));
+
+            // 50% vested
+            set_block_timestamp::<DefaultEnvironment>(start_time + duration / 2);
+            assert_eq!(contract.revoke_partial(1), Ok(50));
+            assert_eq!(contract.locked_of(accounts.bob), 150);
+
+            // 100% vested
+            set_block_timestamp::<DefaultEnvironment>(start_time + duration);
+            assert_eq!(contract.revoke_partial(2), Err(Error::AlreadyVested));
+
+            // The vested half of the revoked schedule stays claimable
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.withdraw_schedule(1), Ok(50));
+            assert_eq!(contract.withdraw_schedule(2), Ok(100));
+            assert_eq!(contract.locked_of(accounts.bob), 0);
+            contract.assert_tvl_invariant();
+        }
+
+        /// Tests the two-step transfer of the admin role.
+        ///
+        /// This test verifies that:
+        /// 1. Only the admin can propose a new admin.
+        /// 2. Only the proposed account can accept the role.
+        /// 3. After acceptance, the new admin holds the privileges and the old one loses them.
+        #[ink::test]
+        fn test_transfer_admin() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            let mut contract = Vesting::new();
+
+            // Act & Assert
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.transfer_admin(accounts.bob), Err(Error::NotAdmin));
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.transfer_admin(accounts.bob), Ok(()));
+            assert_eq!(contract.admin, accounts.alice);
+
+            // Nobody but the proposed account can accept
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.accept_admin(), Err(Error::NotPendingAdmin));
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.accept_admin(), Err(Error::NotPendingAdmin));
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.accept_admin(), Ok(()));
+            assert_eq!(contract.admin, accounts.bob);
+            assert_eq!(contract.pending_admin, None);
+
+            let events: Vec<_> = recorded_events().collect();
+            let event = <AdminTransferred as scale::Decode>::decode(
+                &mut &events[events.len() - 1].data[..]
+            ).expect("Failed to decode AdminTransferred event");
+            assert_eq!(event.old_admin, accounts.alice);
+            assert_eq!(event.new_admin, accounts.bob);
+
+            assert_eq!(contract.pause(), Ok(()));
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.unpause(), Err(Error::NotAdmin));
+        }
+
+        /// Tests looking up a single schedule by ID.
+        ///
+        /// This test verifies that:
+        /// 1. An existing schedule is returned with its details.
+        /// 2. A fully withdrawn schedule is no longer returned.
+        /// 3. A schedule that was never created is not returned.
+        #[ink::test]
+        fn test_get_schedule() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let unlock_time: Timestamp = initial_time + 1000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(0));
+            set_value_transferred::<DefaultEnvironment>(200);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time + 1000), Ok(1));
+
+            set_block_timestamp::<DefaultEnvironment>(unlock_time);
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.withdraw_schedule(0), Ok(100));
+
+            // Act & Assert
+            assert_eq!(
+                contract.get_schedule(1),
+                Some(VestingScheduleInfo {
+                    owner: accounts.alice,
+                    beneficiary: accounts.bob,
+                    amount: 200,
+                    released: 0,
+                    unlock_time: unlock_time + 1000,
+                    unlock_block: None,
+                    claimable: 0,
+                    label: None,
+                    revocable: true,
+                    pending_acceptance: false,
+                })
+            );
+            assert_eq!(contract.get_schedule(0), None);
+            assert_eq!(contract.get_schedule(2), None);
+        }
+
+        /// Tests that the beneficiary entry is dropped once all schedules are withdrawn.
+        ///
+        /// This test verifies that:
+        /// 1. The entry is kept while a schedule is still pending.
+        /// 2. After the last withdrawal the mapping entry is removed.
+        /// 3. The getters return empty results for the beneficiary.
+        #[ink::test]
+        fn test_empty_beneficiary_entry_removed() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let unlock_time: Timestamp = initial_time + 1000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(0));
+            set_value_transferred::<DefaultEnvironment>(200);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time + 1000), Ok(1));
+
+            // Act & Assert
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            set_block_timestamp::<DefaultEnvironment>(unlock_time);
+            assert_eq!(contract.withdraw_schedule(0), Ok(100));
+            assert_eq!(contract.beneficiary_to_ids.get(accounts.bob), Some(vec![1]));
+
+            set_block_timestamp::<DefaultEnvironment>(unlock_time + 1000);
+            assert_eq!(contract.withdraw_fund(), Ok(200));
+            assert!(!contract.beneficiary_to_ids.contains(accounts.bob));
+            assert_eq!(contract.get_schedule_ids_paged(accounts.bob, 0, 10), Vec::<u64>::new());
+            assert_eq!(contract.schedule_count(accounts.bob), 0);
+        }
+
+        /// Tests creating several schedules in a single batch deposit.
+        ///
+        /// This test verifies that:
+        /// 1. A batch whose amounts do not match the transferred value is rejected.
+        /// 2. A matching batch creates one schedule per entry with consecutive IDs.
+        /// 3. The total locked amount equals the transferred value.
+        #[ink::test]
+        fn test_deposit_fund_batch() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let unlock_time: Timestamp = initial_time + 1000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            let entries = vec![
+                (accounts.bob, unlock_time, 100),
+                (accounts.charlie, unlock_time + 1000, 200),
+                (accounts.bob, unlock_time + 2000, 300)
+            ];
+
+            // Act & Assert
+            set_value_transferred::<DefaultEnvironment>(500);
+            assert_eq!(contract.deposit_fund_batch(entries.clone()), Err(Error::AmountMismatch));
+
+            set_value_transferred::<DefaultEnvironment>(600);
+            assert_eq!(contract.deposit_fund_batch(entries), Ok(vec![0, 1, 2]));
+            assert_eq!(contract.total_locked(), 600);
+            assert_eq!(contract.locked_of(accounts.bob), 400);
+            assert_eq!(contract.locked_of(accounts.charlie), 200);
+            assert_eq!(contract.beneficiary_to_ids.get(accounts.bob), Some(vec![0, 2]));
+            assert_eq!(contract.get_schedule(1).map(|info| info.unlock_time), Some(unlock_time + 1000));
+            contract.assert_tvl_invariant();
+        }
+
+        /// Tests withdrawing without any vesting schedule.
+        ///
+        /// This test verifies that:
+        /// 1. An account that never had schedules gets `Error::NoSchedules`.
+        /// 2. After all schedules are withdrawn the error is `Error::NoSchedules` again.
+        #[ink::test]
+        fn test_withdraw_without_schedules() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let unlock_time: Timestamp = initial_time + 1000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            // Act & Assert
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.withdraw_fund(), Err(Error::NoSchedules));
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(0));
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            set_block_timestamp::<DefaultEnvironment>(unlock_time);
+            assert_eq!(contract.withdraw_fund(), Ok(100));
+            assert_eq!(contract.withdraw_fund(), Err(Error::NoSchedules));
+        }
+
+        /// Tests withdrawing while all schedules are still locked.
+        ///
+        /// This test verifies that:
+        /// 1. A beneficiary with only locked schedules gets `Error::NoFundsAvailable`.
+        /// 2. The schedules are left untouched.
+        #[ink::test]
+        fn test_withdraw_with_locked_schedules() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let unlock_time: Timestamp = initial_time + 1000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(0));
+
+            // Act
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            let result = contract.withdraw_fund();
+
+            // Assert
+            assert_eq!(result, Err(Error::NoFundsAvailable));
+            assert_eq!(contract.beneficiary_to_ids.get(accounts.bob), Some(vec![0]));
+            assert_eq!(contract.locked_of(accounts.bob), 100);
+        }
+
+        /// Tests querying the next unlock time of a beneficiary.
+        ///
+        /// This test verifies that:
+        /// 1. Without schedules there is no next unlock.
+        /// 2. The earliest future unlock time is returned, regardless of deposit order.
+        /// 3. Once every unlock time has passed there is no next unlock.
+        #[ink::test]
+        fn test_next_unlock() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            // Act & Assert
+            assert_eq!(contract.next_unlock(accounts.bob), None);
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 3000), Ok(0));
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(1));
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 2000), Ok(2));
+            assert_eq!(contract.next_unlock(accounts.bob), Some(initial_time + 1000));
+
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 1500);
+            assert_eq!(contract.next_unlock(accounts.bob), Some(initial_time + 2000));
+
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 3000);
+            assert_eq!(contract.next_unlock(accounts.bob), None);
+        }
+
+        /// Tests a schedule released in several tranches.
+        ///
+        /// This test verifies that:
+        /// 1. Unordered tranches and mismatching amounts are rejected.
+        /// 2. Each withdrawal releases only the tranches whose time has passed.
+        /// 3. Released tranches are dropped from the stored schedule.
+        /// 4. The last tranche is kept as the unlock time until it is fully released.
+        /// 5. The schedule is removed once the last tranche is released.
+        #[ink::test]
+        fn test_deposit_fund_tranches() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let month: Timestamp = 1000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            let unlocks = vec![
+                (initial_time + month, 100),
+                (initial_time + 2 * month, 200),
+                (initial_time + 3 * month, 300)
+            ];
+
+            let unordered = vec![(initial_time + 2 * month, 300), (initial_time + month, 300)];
+
+            set_value_transferred::<DefaultEnvironment>(600);
+            assert_eq!(contract.deposit_fund_tranches(accounts.bob, unordered), Err(Error::InvalidSchedule));
+            assert_eq!(contract.deposit_fund_tranches(accounts.bob, Vec::new()), Err(Error::InvalidSchedule));
+            set_value_transferred::<DefaultEnvironment>(500);
+            let result = contract.deposit_fund_tranches(accounts.bob, unlocks.clone());
+            assert_eq!(result, Err(Error::AmountMismatch));
+            set_value_transferred::<DefaultEnvironment>(600);
+            assert_eq!(contract.deposit_fund_tranches(accounts.bob, unlocks), Ok(0));
+            assert_eq!(contract.get_schedule(0).map(|info| info.unlock_time), Some(initial_time + 3 * month));
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            let initial_balance = get_account_balance::<DefaultEnvironment>(accounts.bob).expect(
+                "Failed to get initial balance"
+            );
+
+            // Act & Assert
+            assert_eq!(contract.withdraw_fund(), Err(Error::NoFundsAvailable));
+
+            set_block_timestamp::<DefaultEnvironment>(initial_time + month);
+            assert_eq!(contract.withdraw_schedule(0), Ok(100));
+            assert_eq!(
+                contract.schedules.get(0).map(|s| s.kind),
+                Some(VestingKind::Tranches {
+                    tranches: vec![(initial_time + 2 * month, 200), (initial_time + 3 * month, 300)],
+                })
+            );
+
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 2 * month + 1);
+            assert_eq!(contract.get_schedule(0).map(|info| info.claimable), Some(200));
+            assert_eq!(contract.withdraw_schedule(0), Ok(200));
+            assert_eq!(contract.withdraw_fund(), Err(Error::NoFundsAvailable));
+
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 3 * month);
+            assert_eq!(contract.withdraw_partial(100), Ok(100));
+            assert_eq!(contract.get_schedule(0).map(|info| info.unlock_time), Some(initial_time + 3 * month));
+            assert_eq!(contract.withdraw_fund(), Ok(200));
+
+            let final_balance = get_account_balance::<DefaultEnvironment>(accounts.bob).expect(
+                "Failed to get final balance"
+            );
+            assert_eq!(final_balance - initial_balance, 600);
+            assert!(contract.schedules.get(0).is_none());
+            assert_eq!(contract.total_locked(), 0);
+            contract.assert_tvl_invariant();
+        }
+
+        /// Tests withdrawing through a delegate.
+        ///
+        /// This test verifies that:
+        /// 1. An account that is not the delegate cannot withdraw for the beneficiary.
+        /// 2. The registered delegate can trigger the withdrawal.
+        /// 3. The funds are sent to the beneficiary, not to the delegate.
+        #[ink::test]
+        fn test_withdraw_for_delegate() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let unlock_time: Timestamp = initial_time + 1000;
+            let amount: Balance = 100;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(amount);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(0));
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            contract.set_withdraw_delegate(accounts.charlie);
+            set_block_timestamp::<DefaultEnvironment>(unlock_time);
+
+            let bob_balance = get_account_balance::<DefaultEnvironment>(accounts.bob).expect(
+                "Failed to get beneficiary balance"
+            );
+            let charlie_balance = get_account_balance::<DefaultEnvironment>(accounts.charlie).expect(
+                "Failed to get delegate balance"
+            );
+
+            // Act & Assert
+            set_caller::<DefaultEnvironment>(accounts.django);
+            assert_eq!(contract.withdraw_for(accounts.bob), Err(Error::NotAuthorized));
+
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.withdraw_for(accounts.bob), Ok(100));
+
+            let final_bob = get_account_balance::<DefaultEnvironment>(accounts.bob).expect(
+                "Failed to get beneficiary balance"
+            );
+            let final_charlie = get_account_balance::<DefaultEnvironment>(accounts.charlie).expect(
+                "Failed to get delegate balance"
+            );
+            assert_eq!(final_bob - bob_balance, amount);
+            assert_eq!(final_charlie, charlie_balance);
+            assert!(contract.schedules.get(0).is_none());
+            contract.assert_tvl_invariant();
+        }
+
+        /// Tests withdrawing around the exact unlock time.
+        ///
+        /// This test verifies that:
+        /// 1. One millisecond before `unlock_time` the funds are still locked.
+        /// 2. At exactly `unlock_time` the funds can be withdrawn.
+        #[ink::test]
+        fn test_withdraw_at_unlock_boundary() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let unlock_time: Timestamp = initial_time + 1000;
+            let amount: Balance = 100;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(amount);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(0));
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            let initial_balance = get_account_balance::<DefaultEnvironment>(accounts.bob).expect(
+                "Failed to get initial balance"
+            );
+
+            // Act & Assert
+            set_block_timestamp::<DefaultEnvironment>(unlock_time - 1);
+            assert_eq!(contract.withdraw_fund(), Err(Error::NoFundsAvailable));
+
+            set_block_timestamp::<DefaultEnvironment>(unlock_time);
+            assert_eq!(contract.withdraw_fund(), Ok(100));
+
+            let final_balance = get_account_balance::<DefaultEnvironment>(accounts.bob).expect(
+                "Failed to get final balance"
+            );
+            assert_eq!(final_balance - initial_balance, amount);
+        }
+
+        /// Tests freezing deposits while withdrawals keep working.
+        ///
+        /// This test verifies that:
+        /// 1. Only the admin can freeze and unfreeze deposits.
+        /// 2. Deposits are rejected with `Error::DepositsFrozen` while frozen.
+        /// 3. Withdrawals succeed while deposits are frozen.
+        /// 4. Deposits are accepted again after unfreezing.
+        #[ink::test]
+        fn test_freeze_deposits() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let unlock_time: Timestamp = initial_time + 1000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(0));
+
+            // Act & Assert
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.freeze_deposits(), Err(Error::NotAdmin));
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.freeze_deposits(), Ok(()));
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Err(Error::DepositsFrozen));
+            assert_eq!(contract.top_up(0), Err(Error::DepositsFrozen));
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            set_block_timestamp::<DefaultEnvironment>(unlock_time);
+            assert_eq!(contract.withdraw_fund(), Ok(100));
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.unfreeze_deposits(), Ok(()));
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time + 1000), Ok(1));
+        }
+
+        /// Tests restricting schedule creation to whitelisted owners.
+        ///
+        /// This test verifies that:
+        /// 1. Only the admin can manage the whitelist.
+        /// 2. Anyone can create schedules while the whitelist is disabled.
+        /// 3. A non-whitelisted owner is rejected with `Error::NotWhitelisted` once enabled.
+        /// 4. A whitelisted owner can create and top up schedules, until removed.
+        #[ink::test]
+        fn test_owner_whitelist() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let unlock_time: Timestamp = initial_time + 1000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+            set_value_transferred::<DefaultEnvironment>(100);
+
+            // Act & Assert
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_whitelist_enabled(true), Err(Error::NotAdmin));
+            assert_eq!(contract.add_allowed_owner(accounts.bob), Err(Error::NotAdmin));
+            assert_eq!(contract.remove_allowed_owner(accounts.bob), Err(Error::NotAdmin));
+            assert_eq!(contract.deposit_fund(accounts.charlie, unlock_time), Ok(0));
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.set_whitelist_enabled(true), Ok(()));
+            assert_eq!(contract.add_allowed_owner(accounts.charlie), Ok(()));
+            assert!(contract.is_allowed_owner(accounts.charlie));
+            assert!(!contract.is_allowed_owner(accounts.bob));
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.deposit_fund(accounts.charlie, unlock_time), Err(Error::NotWhitelisted));
+            assert_eq!(
+                contract.deposit_fund_linear(accounts.charlie, unlock_time, 1000),
+                Err(Error::NotWhitelisted)
+            );
+            assert_eq!(contract.top_up(0), Err(Error::NotWhitelisted));
+
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(1));
+            assert_eq!(contract.top_up(1), Ok(()));
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.remove_allowed_owner(accounts.charlie), Ok(()));
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Err(Error::NotWhitelisted));
+            assert_eq!(contract.top_up(1), Err(Error::NotWhitelisted));
+            assert_eq!(contract.get_schedule(1).map(|s| s.amount), Some(200));
+        }
+
+        /// Tests depositing a schedule with a label.
+        ///
+        /// This test verifies that:
+        /// 1. A label longer than the limit is rejected.
+        /// 2. A label within the limit is stored with the schedule.
+        /// 3. The label is returned by the schedule getters.
+        #[ink::test]
+        fn test_deposit_fund_labeled() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let unlock_time: Timestamp = initial_time + 1000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+            set_value_transferred::<DefaultEnvironment>(100);
+
+            // Act & Assert
+            let result = contract.deposit_fund_labeled(accounts.bob, unlock_time, vec![b'x'; 65]);
+            assert_eq!(result, Err(Error::LabelTooLong));
+
+            let label = b"Q3 bonus".to_vec();
+            assert_eq!(contract.deposit_fund_labeled(accounts.bob, unlock_time, label.clone()), Ok(0));
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(1));
+
+            assert_eq!(contract.get_schedule(0).and_then(|info| info.label), Some(label));
+            assert_eq!(contract.get_schedule(1).and_then(|info| info.label), None);
+            assert_eq!(contract.get_schedules(accounts.bob)[0].1.label, Some(b"Q3 bonus".to_vec()));
+        }
+
+        /// Tests the index of schedules by owner.
+        ///
+        /// This test verifies that:
+        /// 1. Deposits are listed under the account that created them.
+        /// 2. Revoked schedules are removed from the owner's list.
+        /// 3. Fully withdrawn schedules are removed from the owner's list.
+        #[ink::test]
+        fn test_get_owned_schedule_ids() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let unlock_time: Timestamp = initial_time + 1000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(0));
+            assert_eq!(contract.deposit_fund(accounts.charlie, unlock_time), Ok(1));
+            set_caller::<DefaultEnvironment>(accounts.django);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(2));
+
+            // Act & Assert
+            assert_eq!(contract.get_owned_schedule_ids(accounts.alice), vec![0, 1]);
+            assert_eq!(contract.get_owned_schedule_ids(accounts.django), vec![2]);
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.revoke(1), Ok(()));
+            assert_eq!(contract.get_owned_schedule_ids(accounts.alice), vec![0]);
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            set_block_timestamp::<DefaultEnvironment>(unlock_time);
+            assert_eq!(contract.withdraw_fund(), Ok(200));
+            assert_eq!(contract.get_owned_schedule_ids(accounts.alice), Vec::<u64>::new());
+            assert_eq!(contract.get_owned_schedule_ids(accounts.django), Vec::<u64>::new());
+            assert!(!contract.owner_to_ids.contains(accounts.alice));
+        }
+
+        /// Tests the timelocked emergency withdrawal by the admin.
+        ///
+        /// This test verifies that:
+        /// 1. Only the admin can request and execute an emergency withdrawal.
+        /// 2. Execution without a request or before the delay is rejected.
+        /// 3. After the delay the remaining funds are sent to the admin.
+        /// 4. The schedule is removed from every index.
+        #[ink::test]
+        fn test_emergency_withdraw() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let unlock_time: Timestamp = initial_time + 30 * EMERGENCY_WITHDRAW_DELAY;
+            let amount: Balance = 100;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            set_value_transferred::<DefaultEnvironment>(amount);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(0));
+
+            // Act & Assert
+            assert_eq!(contract.emergency_withdraw_request(0), Err(Error::NotAdmin));
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.emergency_withdraw_execute(0), Err(Error::TimelockNotElapsed));
+            assert_eq!(contract.emergency_withdraw_request(1), Err(Error::ScheduleNotFound));
+            assert_eq!(contract.emergency_withdraw_request(0), Ok(()));
+
+            set_block_timestamp::<DefaultEnvironment>(initial_time + EMERGENCY_WITHDRAW_DELAY - 1);
+            assert_eq!(contract.emergency_withdraw_execute(0), Err(Error::TimelockNotElapsed));
+
+            set_block_timestamp::<DefaultEnvironment>(initial_time + EMERGENCY_WITHDRAW_DELAY);
+            let initial_balance = get_account_balance::<DefaultEnvironment>(accounts.alice).expect(
+                "Failed to get initial balance"
+            );
+            assert_eq!(contract.emergency_withdraw_execute(0), Ok(amount));
+            let final_balance = get_account_balance::<DefaultEnvironment>(accounts.alice).expect(
+                "Failed to get final balance"
+            );
+            assert_eq!(final_balance - initial_balance, amount);
+
+            assert!(contract.schedules.get(0).is_none());
+            assert_eq!(contract.beneficiary_to_ids.get(accounts.bob), None);
+            assert_eq!(contract.get_owned_schedule_ids(accounts.charlie), Vec::<u64>::new());
+            assert_eq!(contract.total_locked(), 0);
+            assert_eq!(contract.emergency_withdraw_execute(0), Err(Error::TimelockNotElapsed));
+            contract.assert_tvl_invariant();
+        }
+
+        /// Tests that an overflowing withdrawal total is reported as such.
+        ///
+        /// This test verifies that:
+        /// 1. Two matured schedules whose amounts add up to more than `u128::MAX`
+        ///    make `withdraw_fund` fail with `Error::AmountOverflow`.
+        /// 2. The error is distinct from `Error::TransferFailed`.
+        #[ink::test]
+        fn test_withdraw_amount_overflow() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            // Seed the schedules directly, no deposit could carry such amounts
+            for id in 0..2 {
+                let schedule = VestingSchedule {
+                    owner: accounts.alice,
+                    beneficiary: accounts.bob,
+                    amount: Balance::MAX / 2 + 1,
+                    released: 0,
+                    kind: VestingKind::Cliff { unlock_time: initial_time },
+                    label: None,
+                    revocable: true,
+                    beneficiary_locked: false,
+                    pending_acceptance: false,
+                };
+                contract.store_schedule(id, &schedule);
+            }
+            contract.beneficiary_to_ids.insert(accounts.bob, &vec![0, 1]);
+
+            // Act
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            let result = contract.withdraw_fund();
+
+            // Assert
+            assert_eq!(result, Err(Error::AmountOverflow));
+        }
+
+        /// Tests withdrawing matured funds to another account.
+        ///
+        /// This test verifies that:
+        /// 1. The funds arrive at the recipient, not at the beneficiary.
+        /// 2. The released schedule is removed from the beneficiary's list.
+        /// 3. Locked schedules stay with the beneficiary.
+        #[ink::test]
+        fn test_withdraw_to() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let unlock_time: Timestamp = initial_time + 1000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(0));
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time + 1000), Ok(1));
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            set_block_timestamp::<DefaultEnvironment>(unlock_time);
+            let bob_balance = get_account_balance::<DefaultEnvironment>(accounts.bob).expect(
+                "Failed to get beneficiary balance"
+            );
+            let eve_balance = get_account_balance::<DefaultEnvironment>(accounts.eve).expect(
+                "Failed to get recipient balance"
+            );
+
+            // Act
+            let result = contract.withdraw_to(accounts.eve);
+
+            // Assert
+            assert_eq!(result, Ok(100));
+            let final_bob = get_account_balance::<DefaultEnvironment>(accounts.bob).expect(
+                "Failed to get beneficiary balance"
+            );
+            let final_eve = get_account_balance::<DefaultEnvironment>(accounts.eve).expect(
+                "Failed to get recipient balance"
+            );
+            assert_eq!(final_bob, bob_balance);
+            assert_eq!(final_eve - eve_balance, 100);
+            assert_eq!(contract.beneficiary_to_ids.get(accounts.bob), Some(vec![1]));
+            assert_eq!(contract.locked_of(accounts.bob), 100);
+        }
+
+        /// Tests the claimable and locked split of a beneficiary's funds.
+        ///
+        /// This test verifies that:
+        /// 1. An account without schedules has nothing claimable or locked.
+        /// 2. Matured cliff schedules count as claimable, future ones as locked.
+        /// 3. A linear schedule is split by its vested portion.
+        /// 4. Withdrawn funds are no longer reported.
+        #[ink::test]
+        fn test_balances_of() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(0));
+            set_value_transferred::<DefaultEnvironment>(200);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 3000), Ok(1));
+            set_value_transferred::<DefaultEnvironment>(400);
+            assert_eq!(contract.deposit_fund_linear(accounts.bob, initial_time, 4000), Ok(2));
+
+            // Act & Assert
+            assert_eq!(contract.balances_of(accounts.charlie), (0, 0));
+            assert_eq!(contract.balances_of(accounts.bob), (0, 700));
+
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 2000);
+            assert_eq!(contract.balances_of(accounts.bob), (300, 400));
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.withdraw_fund(), Ok(300));
+            assert_eq!(contract.balances_of(accounts.bob), (0, 400));
+        }
+
+        /// Tests revoking and reassigning a schedule whose owner is also its beneficiary.
+        ///
+        /// This test verifies that:
+        /// 1. Reassigning moves the schedule and its locked funds exactly once.
+        /// 2. Revoking removes the schedule from both the owner and beneficiary lists.
+        /// 3. The locked counters are decremented exactly once and the refund is not doubled.
+        #[ink::test]
+        fn test_revoke_self_beneficiary() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let unlock_time: Timestamp = initial_time + 1000;
+            let amount: Balance = 100;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_callee::<DefaultEnvironment>(accounts.frank);
+            set_account_balance::<DefaultEnvironment>(accounts.frank, 1_000_000);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(amount);
+            assert_eq!(contract.deposit_fund(accounts.alice, unlock_time), Ok(0));
+            assert_eq!(contract.deposit_fund(accounts.alice, unlock_time), Ok(1));
+            assert_eq!(contract.locked_of(accounts.alice), 2 * amount);
+
+            // Act & Assert
+            assert_eq!(contract.reassign_beneficiary(1, accounts.bob), Ok(()));
+            assert_eq!(contract.beneficiary_to_ids.get(accounts.alice), Some(vec![0]));
+            assert_eq!(contract.locked_of(accounts.alice), amount);
+            assert_eq!(contract.locked_of(accounts.bob), amount);
+            assert_eq!(contract.total_locked(), 2 * amount);
+
+            let initial_balance = get_account_balance::<DefaultEnvironment>(accounts.alice).expect(
+                "Failed to get initial balance"
+            );
+            assert_eq!(contract.revoke(0), Ok(()));
+            let final_balance = get_account_balance::<DefaultEnvironment>(accounts.alice).expect(
+                "Failed to get final balance"
+            );
+            assert_eq!(final_balance - initial_balance, amount);
+
+            assert_eq!(contract.beneficiary_to_ids.get(accounts.alice), None);
+            assert_eq!(contract.get_owned_schedule_ids(accounts.alice), vec![1]);
+            assert_eq!(contract.locked_of(accounts.alice), 0);
+            assert_eq!(contract.total_locked(), amount);
+        }
+
+        /// Tests the protocol fee deducted from withdrawals.
+        ///
+        /// This test verifies that:
+        /// 1. Only the admin can set the fee, and not above the maximum.
+        /// 2. The fee is sent to the fee recipient and the remainder to the beneficiary.
+        /// 3. A zero fee pays the full amount to the beneficiary.
+        #[ink::test]
+        fn test_protocol_fee() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let unlock_time: Timestamp = initial_time + 1000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(1000);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(0));
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time + 1000), Ok(1));
+
+            // Act & Assert
+            assert_eq!(contract.set_fee(MAX_FEE_BPS + 1, accounts.eve), Err(Error::FeeTooHigh));
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_fee(250, accounts.bob), Err(Error::NotAdmin));
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.set_fee(250, accounts.eve), Ok(()));
+
+            let bob_balance = get_account_balance::<DefaultEnvironment>(accounts.bob).expect(
+                "Failed to get beneficiary balance"
+            );
+            let eve_balance = get_account_balance::<DefaultEnvironment>(accounts.eve).expect(
+                "Failed to get fee recipient balance"
+            );
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            set_block_timestamp::<DefaultEnvironment>(unlock_time);
+            assert_eq!(contract.withdraw_fund(), Ok(975));
+
+            let final_bob = get_account_balance::<DefaultEnvironment>(accounts.bob).expect(
+                "Failed to get beneficiary balance"
+            );
+            let final_eve = get_account_balance::<DefaultEnvironment>(accounts.eve).expect(
+                "Failed to get fee recipient balance"
+            );
+            assert_eq!(final_bob - bob_balance, 975);
+            assert_eq!(final_eve - eve_balance, 25);
+
+            // Without a fee the full amount is paid out
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.set_fee(0, accounts.eve), Ok(()));
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            set_block_timestamp::<DefaultEnvironment>(unlock_time + 1000);
+            assert_eq!(contract.withdraw_fund(), Ok(1000));
+            let eve_after = get_account_balance::<DefaultEnvironment>(accounts.eve).expect(
+                "Failed to get fee recipient balance"
+            );
+            assert_eq!(eve_after, final_eve);
+            contract.assert_tvl_invariant();
+        }
+
+        /// Tests checking whether a single schedule is unlocked.
+        ///
+        /// This test verifies that:
+        /// 1. A schedule before its unlock time is reported as locked.
+        /// 2. A schedule at its unlock time is reported as unlocked.
+        /// 3. A schedule that does not exist is reported as locked.
+        #[ink::test]
+        fn test_is_unlocked() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let unlock_time: Timestamp = initial_time + 1000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(0));
+
+            // Act & Assert
+            assert!(!contract.is_unlocked(0));
+
+            set_block_timestamp::<DefaultEnvironment>(unlock_time);
+            assert!(contract.is_unlocked(0));
+            assert!(!contract.is_unlocked(1));
+        }
+
+        /// Tests the storage version of a new contract.
+        ///
+        /// This test verifies that:
+        /// 1. Every constructor initializes the storage version to the current one.
+        #[ink::test]
+        fn test_storage_version() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            set_caller::<DefaultEnvironment>(accounts.alice);
+
+            // Act & Assert
+            assert_eq!(Vesting::new().storage_version(), STORAGE_VERSION);
+            assert_eq!(Vesting::new_with_max_horizon(1000).storage_version(), STORAGE_VERSION);
+            assert_eq!(Vesting::new_psp22(accounts.django).storage_version(), STORAGE_VERSION);
+        }
+
+        /// Tests that a duplicated schedule ID is only paid out once.
+        ///
+        /// This test verifies that:
+        /// 1. A schedule ID listed twice for a beneficiary is released only once.
+        /// 2. The duplicate is dropped from the beneficiary's list.
+        #[ink::test]
+        fn test_withdraw_skips_duplicate_ids() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let unlock_time: Timestamp = initial_time + 1000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(0));
+            assert_eq!(contract.deposit_fund_linear(accounts.bob, initial_time, 2000), Ok(1));
+
+            // Seed the duplicates directly in storage
+            contract.beneficiary_to_ids.insert(accounts.bob, &vec![0, 1, 0, 1]);
+
+            // Act
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            set_block_timestamp::<DefaultEnvironment>(unlock_time);
+            let result = contract.withdraw_fund();
+
+            // Assert
+            assert_eq!(result, Ok(150));
+            assert_eq!(contract.beneficiary_to_ids.get(accounts.bob), Some(vec![1]));
+            assert_eq!(contract.locked_of(accounts.bob), 50);
+        }
+
+        /// Tests revoking revocable and irrevocable schedules.
+        ///
+        /// This test verifies that:
+        /// 1. Schedules are revocable by default and can be revoked by their owner.
+        /// 2. An irrevocable schedule cannot be revoked, fully or partially.
+        /// 3. The irrevocable schedule stays with the beneficiary.
+        #[ink::test]
+        fn test_irrevocable_schedule() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let unlock_time: Timestamp = initial_time + 1000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(0));
+            assert_eq!(contract.deposit_fund_irrevocable(accounts.bob, unlock_time), Ok(1));
+
+            // Act & Assert
+            assert_eq!(contract.get_schedule(0).map(|info| info.revocable), Some(true));
+            assert_eq!(contract.get_schedule(1).map(|info| info.revocable), Some(false));
+
+            assert_eq!(contract.revoke(0), Ok(()));
+            assert_eq!(contract.revoke(1), Err(Error::NotRevocable));
+            assert_eq!(contract.revoke_partial(1), Err(Error::NotRevocable));
+
+            assert_eq!(contract.beneficiary_to_ids.get(accounts.bob), Some(vec![1]));
+            assert_eq!(contract.locked_of(accounts.bob), 100);
+        }
+
+        /// Tests the claimable amount of single schedules of each kind.
+        ///
+        /// This test verifies that:
+        /// 1. A cliff schedule is claimable in full once unlocked.
+        /// 2. A linear schedule is claimable proportionally to the elapsed time.
+        /// 3. A tranche schedule is claimable per passed tranche.
+        /// 4. Released funds and missing schedules report nothing claimable.
+        #[ink::test]
+        fn test_claimable_amount() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(0));
+            set_value_transferred::<DefaultEnvironment>(400);
+            assert_eq!(contract.deposit_fund_linear(accounts.bob, initial_time, 4000), Ok(1));
+            set_value_transferred::<DefaultEnvironment>(300);
+            let unlocks = vec![(initial_time + 500, 100), (initial_time + 1500, 200)];
+            assert_eq!(contract.deposit_fund_tranches(accounts.bob, unlocks), Ok(2));
+
+            // Act & Assert
+            assert_eq!(contract.claimable_amount(0), 0);
+            assert_eq!(contract.claimable_amount(1), 0);
+            assert_eq!(contract.claimable_amount(2), 0);
+
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 1000);
+            assert_eq!(contract.claimable_amount(0), 100);
+            assert_eq!(contract.claimable_amount(1), 100);
+            assert_eq!(contract.claimable_amount(2), 100);
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.withdraw_schedule(1), Ok(100));
+            assert_eq!(contract.claimable_amount(1), 0);
+
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 2000);
+            assert_eq!(contract.claimable_amount(1), 100);
+            assert_eq!(contract.claimable_amount(2), 300);
+            assert_eq!(contract.claimable_amount(3), 0);
+        }
+
+        /// Tests sweeping native balance that no schedule accounts for.
+        ///
+        /// This test verifies that:
+        /// 1. Only the admin can sweep.
+        /// 2. Only the balance above the locked funds is sent to the admin.
+        /// 3. A second sweep finds nothing left to sweep.
+        #[ink::test]
+        fn test_sweep_untracked() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let unlock_time: Timestamp = initial_time + 1000;
+            let contract_account = accounts.django;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_callee::<DefaultEnvironment>(contract_account);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            // A tracked deposit of 100 and raw value sent on top of it
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(0));
+            set_account_balance::<DefaultEnvironment>(contract_account, 1_000_100);
+
+            // Act & Assert
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.sweep_untracked(), Err(Error::NotAdmin));
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            let initial_balance = get_account_balance::<DefaultEnvironment>(accounts.alice).expect(
+                "Failed to get initial balance"
+            );
+            assert_eq!(contract.sweep_untracked(), Ok(1_000_000));
+            let final_balance = get_account_balance::<DefaultEnvironment>(accounts.alice).expect(
+                "Failed to get final balance"
+            );
+            assert_eq!(final_balance - initial_balance, 1_000_000);
+            assert_eq!(get_account_balance::<DefaultEnvironment>(contract_account), Ok(100));
+            assert_eq!(contract.sweep_untracked(), Err(Error::NoFundsAvailable));
+        }
+
+        /// Tests the cooldown between two withdrawals of a beneficiary.
+        ///
+        /// This test verifies that:
+        /// 1. Only the admin can set the cooldown.
+        /// 2. A withdrawal before the cooldown has passed is rejected.
+        /// 3. A withdrawal after the cooldown succeeds.
+        /// 4. Withdrawing by schedule ID or in pages is subject to the same cooldown.
+        #[ink::test]
+        fn test_withdraw_cooldown() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let cooldown: Timestamp = 500;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(0));
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1100), Ok(1));
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 2000), Ok(2));
+
+            // Act & Assert
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_cooldown(cooldown), Err(Error::NotAdmin));
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.set_cooldown(cooldown), Ok(()));
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 1000);
+            assert_eq!(contract.withdraw_fund(), Ok(100));
+
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 1000 + cooldown - 1);
+            assert_eq!(contract.withdraw_fund(), Err(Error::CooldownActive));
+            assert_eq!(contract.withdraw_schedule(1), Err(Error::CooldownActive));
+            assert_eq!(contract.withdraw_fund_limited(10), Err(Error::CooldownActive));
+            assert_eq!(contract.withdraw_partial(1), Err(Error::CooldownActive));
+
+            // Each path starts the cooldown for the others
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 2000);
+            assert_eq!(contract.withdraw_schedule(1), Ok(100));
+            assert_eq!(contract.withdraw_fund_limited(10), Err(Error::CooldownActive));
+
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 2000 + cooldown);
+            assert_eq!(contract.withdraw_fund_limited(10), Ok(100));
+            assert_eq!(contract.withdraw_fund(), Err(Error::NoSchedules));
+            contract.assert_tvl_invariant();
+        }
+
+        /// Tests instantiating the contract with an initial allocation table.
+        ///
+        /// This test verifies that:
+        /// 1. Instantiation fails if the endowment does not match the entry amounts.
+        /// 2. Every entry becomes a schedule owned by the deployer.
+        /// 3. The schedules are indexed by beneficiary and owner.
+        #[ink::test]
+        fn test_new_with_schedules() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let unlock_time: Timestamp = initial_time + 1000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+
+            let entries = vec![
+                (accounts.bob, unlock_time, 100),
+                (accounts.charlie, unlock_time + 1000, 200),
+                (accounts.bob, unlock_time + 2000, 300)
+            ];
+
+            // Act & Assert
+            set_value_transferred::<DefaultEnvironment>(500);
+            assert!(matches!(Vesting::new_with_schedules(entries.clone()), Err(Error::AmountMismatch)));
+
+            set_value_transferred::<DefaultEnvironment>(600);
+            let contract = Vesting::new_with_schedules(entries).expect("Failed to instantiate");
+
+            assert_eq!(contract.total_locked(), 600);
+            assert_eq!(contract.get_schedule_ids_paged(accounts.bob, 0, 10), vec![0, 2]);
+            assert_eq!(contract.get_schedule_ids_paged(accounts.charlie, 0, 10), vec![1]);
+            assert_eq!(contract.get_owned_schedule_ids(accounts.alice), vec![0, 1, 2]);
+            assert_eq!(contract.get_schedule(1).map(|info| info.amount), Some(200));
+        }
+
+        /// Tests that an event is emitted for every fully drained schedule.
+        ///
+        /// This test verifies that:
+        /// 1. A fully withdrawn schedule emits a `ScheduleClosed` event.
+        /// 2. A partially withdrawn linear schedule does not.
+        /// 3. The linear schedule emits the event once fully withdrawn.
+        #[ink::test]
+        fn test_schedule_closed_event() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(0));
+            assert_eq!(contract.deposit_fund_linear(accounts.bob, initial_time, 2000), Ok(1));
+
+            // Act
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 1000);
+            assert_eq!(contract.withdraw_fund(), Ok(150));
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 2000);
+            assert_eq!(contract.withdraw_fund(), Ok(50));
+
+            // Assert
+            // Deposited, Deposited, ScheduleClosed, Withdrawn, ScheduleClosed, Withdrawn
+            let events: Vec<_> = recorded_events().collect();
+            assert_eq!(events.len(), 6);
+            for (index, id) in [(2, 0), (4, 1)] {
+                let event = <ScheduleClosed as scale::Decode>::decode(&mut &events[index].data[..]).expect(
+                    "Failed to decode ScheduleClosed event"
+                );
+                assert_eq!(event.id, id);
+                assert_eq!(event.beneficiary, accounts.bob);
+            }
+        }
+
+        /// Tests rejecting beneficiaries that could never claim their funds.
+        ///
+        /// This test verifies that:
+        /// 1. Deposits to the zero address are rejected.
+        /// 2. Deposits to the contract's own address are rejected.
+        /// 3. A schedule cannot be reassigned to either of them.
+        #[ink::test]
+        fn test_invalid_beneficiary() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let unlock_time: Timestamp = initial_time + 1000;
+            let zero_address = AccountId::from([0u8; 32]);
+            let contract_account = accounts.django;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_callee::<DefaultEnvironment>(contract_account);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+            set_value_transferred::<DefaultEnvironment>(100);
+
+            // Act & Assert
+            assert_eq!(contract.deposit_fund(zero_address, unlock_time), Err(Error::InvalidBeneficiary));
+            assert_eq!(contract.deposit_fund(contract_account, unlock_time), Err(Error::InvalidBeneficiary));
+
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(0));
+            assert_eq!(contract.reassign_beneficiary(0, zero_address), Err(Error::InvalidBeneficiary));
+            assert_eq!(contract.reassign_beneficiary(0, contract_account), Err(Error::InvalidBeneficiary));
+            assert_eq!(contract.beneficiary_to_ids.get(accounts.bob), Some(vec![0]));
+        }
+
+        /// Tests the contract-wide statistics.
+        ///
+        /// This test verifies that:
+        /// 1. A new contract reports empty statistics.
+        /// 2. Deposits increase the schedule count, the next ID and the locked total.
+        /// 3. Withdrawals and revocations are reflected in the counters.
+        #[ink::test]
+        fn test_stats() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            // Act & Assert
+            assert_eq!(contract.stats(), ContractStats {
+                total_schedules: 0,
+                next_id: 0,
+                total_locked: 0,
+                total_withdrawn: 0,
+            });
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(0));
+            set_value_transferred::<DefaultEnvironment>(200);
+            assert_eq!(contract.deposit_fund_linear(accounts.bob, initial_time, 2000), Ok(1));
+            set_value_transferred::<DefaultEnvironment>(300);
+            assert_eq!(contract.deposit_fund(accounts.charlie, initial_time + 3000), Ok(2));
+            assert_eq!(contract.revoke(2), Ok(()));
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 1000);
+            assert_eq!(contract.withdraw_fund(), Ok(200));
+
+            assert_eq!(contract.stats(), ContractStats {
+                total_schedules: 1,
+                next_id: 3,
+                total_locked: 100,
+                total_withdrawn: 200,
+            });
+        }
+
+        /// Tests a beneficiary renouncing a schedule.
+        ///
+        /// This test verifies that:
+        /// 1. Only the beneficiary can renounce the schedule.
+        /// 2. The funds are returned to the owner.
+        /// 3. The schedule is removed from the beneficiary's list.
+        #[ink::test]
+        fn test_renounce() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let unlock_time: Timestamp = initial_time + 1000;
+            let amount: Balance = 100;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            set_value_transferred::<DefaultEnvironment>(amount);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(0));
+
+            // Act & Assert
+            assert_eq!(contract.renounce(0), Err(Error::NotBeneficiary));
+
+            let initial_balance = get_account_balance::<DefaultEnvironment>(accounts.charlie).expect(
+                "Failed to get initial balance"
+            );
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.renounce(0), Ok(()));
+            let final_balance = get_account_balance::<DefaultEnvironment>(accounts.charlie).expect(
+                "Failed to get final balance"
+            );
+            assert_eq!(final_balance - initial_balance, amount);
+
+            assert!(contract.schedules.get(0).is_none());
+            assert_eq!(contract.beneficiary_to_ids.get(accounts.bob), None);
+            assert_eq!(contract.get_owned_schedule_ids(accounts.charlie), Vec::<u64>::new());
+            assert_eq!(contract.total_locked(), 0);
+            contract.assert_tvl_invariant();
+        }
+
+        /// Tests the maximum number of schedules per beneficiary.
+        ///
+        /// This test verifies that:
+        /// 1. Only the admin can set the limit.
+        /// 2. Deposits are accepted up to the limit and rejected beyond it.
+        /// 3. Other beneficiaries are not affected.
+        /// 4. A zero limit removes the restriction.
+        #[ink::test]
+        fn test_max_schedules_per_beneficiary() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let unlock_time: Timestamp = initial_time + 1000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+            set_value_transferred::<DefaultEnvironment>(100);
+
+            // Act & Assert
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_max_schedules_per_beneficiary(2), Err(Error::NotAdmin));
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.set_max_schedules_per_beneficiary(2), Ok(()));
+
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(0));
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(1));
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Err(Error::TooManySchedules));
+            assert_eq!(contract.deposit_fund(accounts.charlie, unlock_time), Ok(2));
+            assert_eq!(contract.reassign_beneficiary(2, accounts.bob), Err(Error::TooManySchedules));
+
+            assert_eq!(contract.set_max_schedules_per_beneficiary(0), Ok(()));
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(3));
+        }
+
+        /// Tests the maximum amount of a single schedule.
+        ///
+        /// This test verifies that:
+        /// 1. Only the admin can set the limit.
+        /// 2. Deposits up to the limit are accepted and larger ones rejected.
+        /// 3. A top-up cannot push a schedule beyond the limit.
+        /// 4. A zero limit removes the restriction.
+        #[ink::test]
+        fn test_max_schedule_amount() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let unlock_time: Timestamp = initial_time + 1000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            // Act & Assert
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_max_schedule_amount(100), Err(Error::NotAdmin));
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.set_max_schedule_amount(100), Ok(()));
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(0));
+            set_value_transferred::<DefaultEnvironment>(101);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Err(Error::AmountTooLarge));
+            set_value_transferred::<DefaultEnvironment>(1);
+            assert_eq!(contract.top_up(0), Err(Error::AmountTooLarge));
+
+            assert_eq!(contract.set_max_schedule_amount(0), Ok(()));
+            set_value_transferred::<DefaultEnvironment>(101);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(1));
+        }
+
+        /// Tests notifying a contract of withdrawals.
+        ///
+        /// This test verifies that:
+        /// 1. Only the admin can set the notification contract, changes emit `ConfigAccountChanged`.
+        /// 2. The notification contract is called with the beneficiary and amount.
+        /// 3. Withdrawals by schedule ID and in pages are notified too.
+        /// 4. A failing notification emits `NotifyFailed` but does not block the withdrawal.
+        #[ink::test]
+        fn test_withdraw_notification() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let observer = AccountId::from([0x07; 32]);
+            let failing_observer = AccountId::from([0x08; 32]);
+            mock_notify::fail(failing_observer);
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(0));
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 2000), Ok(1));
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(2));
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(3));
+
+            // Act & Assert
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_notify_contract(Some(observer)), Err(Error::NotAdmin));
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.set_notify_contract(Some(observer)), Ok(()));
+            let events: Vec<_> = recorded_events().collect();
+            let event = <ConfigAccountChanged as scale::Decode>::decode(
+                &mut &events[events.len() - 1].data[..]
+            ).expect("Failed to decode ConfigAccountChanged event");
+            assert_eq!(event.param, ConfigParam::NotifyContract as u8);
+            assert_eq!((event.old_account, event.new_account), (None, Some(observer)));
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 1000);
+            assert_eq!(contract.withdraw_fund(), Ok(300));
+            assert_eq!(mock_notify::calls(), vec![(observer, accounts.bob, 300)]);
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1500), Ok(4));
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1500), Ok(5));
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 1500);
+            assert_eq!(contract.withdraw_schedule(4), Ok(100));
+            assert_eq!(contract.withdraw_fund_limited(10), Ok(100));
+            assert_eq!(contract.withdraw_fund_limited(10), Ok(0));
+            assert_eq!(mock_notify::calls(), vec![
+                (observer, accounts.bob, 300),
+                (observer, accounts.bob, 100),
+                (observer, accounts.bob, 100)
+            ]);
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.set_notify_contract(Some(failing_observer)), Ok(()));
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 2000);
+            assert_eq!(contract.withdraw_fund(), Ok(100));
+
+            let events: Vec<_> = recorded_events().collect();
+            let event = <NotifyFailed as scale::Decode>::decode(
+                &mut &events[events.len() - 1].data[..]
+            ).expect("Failed to decode NotifyFailed event");
+            assert_eq!(event.notify_contract, failing_observer);
+            assert_eq!(event.beneficiary, accounts.bob);
+            assert_eq!(event.amount, 100);
+        }
+
+        /// Tests previewing the schedules a withdrawal would release.
+        ///
+        /// This test verifies that:
+        /// 1. Without schedules nothing is withdrawable.
+        /// 2. Only matured or partially vested schedules are returned, locked ones are not.
+        /// 3. The preview does not change state and matches the released schedules.
+        #[ink::test]
+        fn test_withdrawable_ids() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            // Act & Assert
+            assert_eq!(contract.withdrawable_ids(accounts.bob), Vec::<u64>::new());
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(0));
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 3000), Ok(1));
+            assert_eq!(contract.deposit_fund_linear(accounts.bob, initial_time, 4000), Ok(2));
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 2000), Ok(3));
+
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 2000);
+            assert_eq!(contract.withdrawable_ids(accounts.bob), vec![0, 2, 3]);
+            assert_eq!(contract.withdrawable_ids(accounts.bob), vec![0, 2, 3]);
+            assert_eq!(contract.get_schedules(accounts.bob).len(), 4);
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.withdraw_fund(), Ok(250));
+            assert_eq!(contract.withdrawable_ids(accounts.bob), Vec::<u64>::new());
+
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 3000);
+            assert_eq!(contract.withdrawable_ids(accounts.bob), vec![1, 2]);
+        }
+
+        /// Tests depositing an explicit amount decoupled from the transferred value.
+        ///
+        /// This test verifies that:
+        /// 1. A transferred value below the amount is rejected.
+        /// 2. An exact transferred value locks the full amount without a refund.
+        /// 3. A surplus is refunded to the caller and only the amount is locked.
+        #[ink::test]
+        fn test_deposit_fund_exact() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let unlock_time = initial_time + 1000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            let initial_balance = get_account_balance::<DefaultEnvironment>(accounts.charlie).expect(
+                "Failed to get initial balance"
+            );
+
+            // Act & Assert
+            set_value_transferred::<DefaultEnvironment>(99);
+            assert_eq!(
+                contract.deposit_fund_exact(accounts.bob, unlock_time, 100),
+                Err(Error::InsufficientValue)
+            );
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund_exact(accounts.bob, unlock_time, 100), Ok(0));
+            assert_eq!(contract.get_schedule(0).map(|s| s.amount), Some(100));
+            assert_eq!(
+                get_account_balance::<DefaultEnvironment>(accounts.charlie),
+                Ok(initial_balance)
+            );
+
+            set_value_transferred::<DefaultEnvironment>(150);
+            assert_eq!(contract.deposit_fund_exact(accounts.bob, unlock_time, 100), Ok(1));
+            assert_eq!(contract.get_schedule(1).map(|s| s.amount), Some(100));
+            assert_eq!(
+                get_account_balance::<DefaultEnvironment>(accounts.charlie),
+                Ok(initial_balance + 50)
+            );
+            assert_eq!(contract.total_locked(), 200);
+        }
+
+        /// Tests rebuilding a corrupted beneficiary index.
+        ///
+        /// This test verifies that:
+        /// 1. Only the admin can rebuild an index.
+        /// 2. Unknown, duplicated and foreign IDs are dropped from the candidates.
+        /// 3. Schedules missing from the index are withdrawable again once repaired.
+        #[ink::test]
+        fn test_rebuild_beneficiary_index() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(0));
+            assert_eq!(contract.deposit_fund(accounts.charlie, initial_time + 1000), Ok(1));
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(2));
+
+            // Seed a broken index that lost schedule 0 and points at unrelated schedules
+            contract.beneficiary_to_ids.insert(accounts.bob, &vec![2, 1, 7]);
+
+            // Act & Assert
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.rebuild_beneficiary_index(accounts.bob, vec![0, 2]), Err(Error::NotAdmin));
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.rebuild_beneficiary_index(accounts.bob, vec![2, 0, 1, 7, 2]), Ok(()));
+            assert_eq!(contract.beneficiary_to_ids.get(accounts.bob), Some(vec![2, 0]));
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 1000);
+            assert_eq!(contract.withdraw_fund(), Ok(200));
+            assert_eq!(contract.get_schedule(1).map(|s| s.beneficiary), Some(accounts.charlie));
+        }
+
+        /// Tests pruning stale entries from the active schedule index.
+        ///
+        /// This test verifies that:
+        /// 1. Only the admin can prune.
+        /// 2. Only IDs without a stored schedule are removed from the index.
+        /// 3. The index is consistent again after pruning.
+        #[ink::test]
+        fn test_prune() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(0));
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(1));
+
+            // Seed a stale index entry for a schedule that does not exist, in the middle
+            contract.active_ids.insert(2, &1);
+            contract.active_positions.insert(1, &2);
+            contract.active_ids.insert(1, &7);
+            contract.active_positions.insert(7, &1);
+            contract.schedule_count = 3;
+
+            // Act & Assert
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.prune(vec![7]), Err(Error::NotAdmin));
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.prune(vec![0, 7, 9]), Ok(1));
+            assert_eq!(contract.stats().total_schedules, 2);
+            assert_eq!(contract.active_positions.get(7), None);
+            contract.assert_tvl_invariant();
+            assert_eq!(contract.prune(vec![7]), Ok(0));
+        }
+
+        /// Tests moving a schedule to a new owner.
+        ///
+        /// This test verifies that:
+        /// 1. Only the admin can reassign the owner.
+        /// 2. The schedule leaves the old owner's index and joins the new owner's.
+        /// 3. Only the new owner can manage the schedule afterwards.
+        #[ink::test]
+        fn test_reassign_owner() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(0));
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(1));
+
+            // Act & Assert
+            assert_eq!(contract.reassign_owner(0, accounts.eve), Err(Error::NotAdmin));
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.reassign_owner(7, accounts.eve), Err(Error::ScheduleNotFound));
+            assert_eq!(contract.reassign_owner(0, accounts.eve), Ok(()));
+            assert_eq!(contract.get_owned_schedule_ids(accounts.charlie), vec![1]);
+            assert_eq!(contract.get_owned_schedule_ids(accounts.eve), vec![0]);
+            assert_eq!(contract.get_schedule(0).map(|s| s.owner), Some(accounts.eve));
+
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.revoke(0), Err(Error::NotOwner));
+            set_caller::<DefaultEnvironment>(accounts.eve);
+            let initial_balance = get_account_balance::<DefaultEnvironment>(accounts.eve).expect(
+                "Failed to get initial balance"
+            );
+            assert_eq!(contract.revoke(0), Ok(()));
+            assert_eq!(get_account_balance::<DefaultEnvironment>(accounts.eve), Ok(initial_balance + 100));
+            assert_eq!(contract.get_owned_schedule_ids(accounts.eve), Vec::<u64>::new());
+            contract.assert_tvl_invariant();
+        }
+
+        /// Tests depositing funds that unlock a given duration from now.
+        ///
+        /// This test verifies that:
+        /// 1. The unlock time is the current block timestamp plus the duration.
+        /// 2. A zero duration is rejected as already unlocked.
+        /// 3. A duration overflowing the timestamp is rejected.
+        #[ink::test]
+        fn test_deposit_fund_after() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+            set_value_transferred::<DefaultEnvironment>(100);
+
+            // Act & Assert
+            assert_eq!(contract.deposit_fund_after(accounts.bob, 1000), Ok(0));
+            assert_eq!(contract.get_schedule(0).map(|s| s.unlock_time), Some(initial_time + 1000));
+
+            assert_eq!(contract.deposit_fund_after(accounts.bob, 0), Err(Error::UnlockInPast));
+            assert_eq!(contract.deposit_fund_after(accounts.bob, u64::MAX), Err(Error::UnlockTooFar));
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 999);
+            assert_eq!(contract.withdraw_fund(), Err(Error::NoFundsAvailable));
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 1000);
+            assert_eq!(contract.withdraw_fund(), Ok(100));
+        }
+
+        /// Tests exporting all active schedules for auditing.
+        ///
+        /// This test verifies that:
+        /// 1. Deposits of every beneficiary are exported.
+        /// 2. Withdrawn, revoked and renounced schedules are no longer exported.
+        /// 3. Pages are bounded by the number of active schedules.
+        #[ink::test]
+        fn test_export_schedules() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let exported_ids = |contract: &Vesting| -> Vec<u64> {
+                let mut ids: Vec<u64> = contract
+                    .export_schedules(0, 10)
+                    .into_iter()
+                    .map(|(id, _)| id)
+                    .collect();
+                ids.sort();
+                ids
+            };
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(0));
+            assert_eq!(contract.deposit_fund(accounts.charlie, initial_time + 2000), Ok(1));
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 2000), Ok(2));
+            assert_eq!(contract.deposit_fund(accounts.django, initial_time + 2000), Ok(3));
+            assert_eq!(contract.deposit_fund(accounts.eve, initial_time + 2000), Ok(4));
+
+            // Act & Assert
+            assert_eq!(exported_ids(&contract), vec![0, 1, 2, 3, 4]);
+            assert_eq!(contract.export_schedules(0, 2).len(), 2);
+            assert_eq!(contract.export_schedules(4, 10).len(), 1);
+            assert_eq!(contract.export_schedules(5, 10), Vec::new());
+
+            assert_eq!(contract.revoke(1), Ok(()));
+            assert_eq!(exported_ids(&contract), vec![0, 2, 3, 4]);
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 1000);
+            assert_eq!(contract.withdraw_fund(), Ok(100));
+            assert_eq!(exported_ids(&contract), vec![2, 3, 4]);
+
+            set_caller::<DefaultEnvironment>(accounts.eve);
+            assert_eq!(contract.renounce(4), Ok(()));
+            assert_eq!(exported_ids(&contract), vec![2, 3]);
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 2000), Ok(5));
+            assert_eq!(exported_ids(&contract), vec![2, 3, 5]);
+            let exported = contract.export_schedules(0, 10);
+            assert_eq!(exported.len(), 3);
+            assert!(exported.iter().all(|(id, info)| contract.get_schedule(*id).as_ref() == Some(info)));
+            contract.assert_tvl_invariant();
+        }
+
+        /// Tests postponing the unlock time of a schedule.
+        ///
+        /// This test verifies that:
+        /// 1. Only the owner can extend a schedule.
+        /// 2. The unlock time cannot be kept or brought forward.
+        /// 3. An extended schedule cannot be withdrawn before its new unlock time.
+        /// 4. A schedule that is already unlocked cannot be extended.
+        #[ink::test]
+        fn test_extend_unlock() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let unlock_time = initial_time + 1000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(0));
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(1));
+
+            // Act & Assert
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.extend_unlock(0, unlock_time + 1000), Err(Error::NotOwner));
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.extend_unlock(0, unlock_time - 500), Err(Error::CannotShorten));
+            assert_eq!(contract.extend_unlock(0, unlock_time), Err(Error::CannotShorten));
+            assert_eq!(contract.extend_unlock(0, unlock_time + 1000), Ok(()));
+            assert_eq!(contract.get_schedule(0).map(|s| s.unlock_time), Some(unlock_time + 1000));
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            set_block_timestamp::<DefaultEnvironment>(unlock_time);
+            assert_eq!(contract.withdraw_fund(), Ok(100));
+            assert_eq!(contract.get_schedule(0).map(|s| s.amount), Some(100));
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.extend_unlock(0, unlock_time + 2000), Ok(()));
+            set_block_timestamp::<DefaultEnvironment>(unlock_time + 2000);
+            assert_eq!(contract.extend_unlock(0, unlock_time + 3000), Err(Error::AlreadyVested));
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.withdraw_fund(), Ok(100));
+        }
+
+        /// Tests revoking all schedules of a beneficiary at once.
+        ///
+        /// This test verifies that:
+        /// 1. Only the schedules created by the caller are revoked.
+        /// 2. Irrevocable and already vesting schedules are kept.
+        /// 3. The total refunded is returned and nothing is left to revoke afterwards.
+        #[ink::test]
+        fn test_revoke_all_for() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 2000), Ok(0));
+            assert_eq!(contract.deposit_fund_irrevocable(accounts.bob, initial_time + 2000), Ok(1));
+            assert_eq!(contract.deposit_fund_linear(accounts.bob, initial_time, 4000), Ok(2));
+            set_value_transferred::<DefaultEnvironment>(200);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 3000), Ok(3));
+            assert_eq!(contract.deposit_fund(accounts.charlie, initial_time + 3000), Ok(4));
+
+            set_caller::<DefaultEnvironment>(accounts.django);
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 2000), Ok(5));
+
+            // Act
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 1000);
+            let refunded = contract.revoke_all_for(accounts.bob);
+
+            // Assert
+            assert_eq!(refunded, Ok(300));
+            assert!(contract.get_schedule(0).is_none());
+            assert!(contract.get_schedule(3).is_none());
+            assert_eq!(contract.beneficiary_to_ids.get(accounts.bob), Some(vec![1, 2, 5]));
+            assert_eq!(contract.get_owned_schedule_ids(accounts.alice), vec![1, 2, 4]);
+            assert_eq!(contract.locked_of(accounts.bob), 300);
+            assert_eq!(contract.locked_of(accounts.charlie), 200);
+            assert_eq!(contract.revoke_all_for(accounts.bob), Ok(0));
+            contract.assert_tvl_invariant();
+        }
+
+        /// Tests the claim history of a beneficiary.
+        ///
+        /// This test verifies that:
+        /// 1. Each withdrawal appends its time and amount to the history.
+        /// 2. Failed withdrawals are not recorded.
+        /// 3. Only the most recent claims are kept.
+        #[ink::test]
+        fn test_claim_history() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(0));
+            assert_eq!(contract.deposit_fund_linear(accounts.bob, initial_time, 4000), Ok(1));
+            assert_eq!(contract.get_claim_history(accounts.bob), Vec::new());
+
+            // Act & Assert
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 1000);
+            assert_eq!(contract.withdraw_fund(), Ok(125));
+            assert_eq!(contract.withdraw_fund(), Err(Error::NoFundsAvailable));
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 3000);
+            assert_eq!(contract.withdraw_schedule(1), Ok(50));
+            assert_eq!(
+                contract.get_claim_history(accounts.bob),
+                vec![(initial_time + 1000, 125), (initial_time + 3000, 50)]
+            );
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_value_transferred::<DefaultEnvironment>(60);
+            assert_eq!(contract.deposit_fund_linear(accounts.bob, initial_time + 3000, 60), Ok(2));
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            for step in 1..=60 {
+                set_block_timestamp::<DefaultEnvironment>(initial_time + 3000 + step);
+                assert_eq!(contract.withdraw_schedule(2), Ok(1));
+            }
+            let history = contract.get_claim_history(accounts.bob);
+            assert_eq!(history.len(), MAX_CLAIM_HISTORY);
+            assert_eq!(history.first(), Some(&(initial_time + 3011, 1)));
+            assert_eq!(history.last(), Some(&(initial_time + 3060, 1)));
+        }
+
+        /// Tests the average delay between unlock times and claims.
+        ///
+        /// This test verifies that:
+        /// 1. No average is reported before a schedule is fully claimed.
+        /// 2. A claim at the unlock time counts as no delay.
+        /// 3. Later claims are averaged with the earlier ones.
+        /// 4. Partial claims of a linear schedule only count once it is fully released.
+        /// 5. A tranche schedule counts from its last tranche.
+        #[ink::test]
+        fn test_average_claim_delay() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(0));
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 2000), Ok(1));
+            assert_eq!(contract.deposit_fund_linear(accounts.bob, initial_time, 3000), Ok(2));
+            assert_eq!(contract.average_claim_delay(accounts.bob), None);
+
+            // Act & Assert
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 1000);
+            assert_eq!(contract.withdraw_schedule(0), Ok(100));
+            assert_eq!(contract.average_claim_delay(accounts.bob), Some(0));
+
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 2600);
+            assert_eq!(contract.withdraw_schedule(1), Ok(100));
+            assert_eq!(contract.average_claim_delay(accounts.bob), Some(300));
+
+            assert_eq!(contract.withdraw_schedule(2), Ok(86));
+            assert_eq!(contract.average_claim_delay(accounts.bob), Some(300));
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 3900);
+            assert_eq!(contract.withdraw_fund(), Ok(14));
+            assert_eq!(contract.average_claim_delay(accounts.bob), Some(500));
+            assert_eq!(contract.average_claim_delay(accounts.charlie), None);
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            let tranches = vec![(initial_time + 4000, 40), (initial_time + 5000, 60)];
+            assert_eq!(contract.deposit_fund_tranches(accounts.bob, tranches), Ok(3));
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 5100);
+            assert_eq!(contract.withdraw_schedule(3), Ok(100));
+            assert_eq!(contract.average_claim_delay(accounts.bob), Some(400));
+        }
+
+        /// Tests simulating a withdrawal before performing it.
+        ///
+        /// This test verifies that:
+        /// 1. The simulation fails with the same errors as the withdrawal.
+        /// 2. The simulated amount, after the protocol fee, matches the amount paid.
+        /// 3. The simulation does not change any state.
+        #[ink::test]
+        fn test_simulate_withdraw() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+            assert_eq!(contract.set_fee(100, accounts.eve), Ok(()));
+
+            set_value_transferred::<DefaultEnvironment>(1000);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(0));
+            assert_eq!(contract.deposit_fund_linear(accounts.bob, initial_time, 4000), Ok(1));
+
+            // Act & Assert
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.simulate_withdraw(accounts.charlie), Err(Error::NoSchedules));
+
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 1000);
+            assert_eq!(contract.simulate_withdraw(accounts.bob), Ok(1238));
+            assert_eq!(contract.simulate_withdraw(accounts.bob), Ok(1238));
+            assert_eq!(contract.total_locked(), 2000);
+            assert_eq!(contract.withdraw_fund(), Ok(1238));
+
+            assert_eq!(contract.simulate_withdraw(accounts.bob), Err(Error::NoFundsAvailable));
+            assert_eq!(contract.withdraw_fund(), Err(Error::NoFundsAvailable));
+
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 3000);
+            let simulated = contract.simulate_withdraw(accounts.bob);
+            assert_eq!(simulated, Ok(495));
+            assert_eq!(contract.withdraw_fund(), simulated);
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.pause(), Ok(()));
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 4000);
+            assert_eq!(contract.simulate_withdraw(accounts.bob), Err(Error::Paused));
+            assert_eq!(contract.withdraw_fund(), Err(Error::Paused));
+        }
+
+        /// Tests the lifetime total received by a beneficiary.
+        ///
+        /// This test verifies that:
+        /// 1. The total is zero before any withdrawal.
+        /// 2. Each withdrawal adds the amount paid to the total.
+        /// 3. The total is kept after the schedules are fully withdrawn.
+        #[ink::test]
+        fn test_total_received_of() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(0));
+            set_value_transferred::<DefaultEnvironment>(200);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 2000), Ok(1));
+
+            // Act & Assert
+            assert_eq!(contract.total_received_of(accounts.bob), 0);
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 1000);
+            assert_eq!(contract.withdraw_fund(), Ok(100));
+            assert_eq!(contract.total_received_of(accounts.bob), 100);
+
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 2000);
+            assert_eq!(contract.withdraw_fund(), Ok(200));
+            assert_eq!(contract.total_received_of(accounts.bob), 300);
+            assert_eq!(contract.balances_of(accounts.bob), (0, 0));
+            assert_eq!(contract.total_received_of(accounts.charlie), 0);
+        }
+
+        /// Tests choosing the admin at instantiation.
+        ///
+        /// This test verifies that:
+        /// 1. `new` makes the deployer the admin.
+        /// 2. `new_with_admin` makes the given account the admin, not the deployer.
+        #[ink::test]
+        fn test_new_with_admin() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            set_caller::<DefaultEnvironment>(accounts.alice);
+
+            // Act
+            let default_contract = Vesting::new();
+            let mut contract = Vesting::new_with_admin(accounts.bob);
+
+            // Assert
+            assert_eq!(default_contract.admin(), accounts.alice);
+            assert_eq!(contract.admin(), accounts.bob);
+            assert_eq!(contract.pause(), Err(Error::NotAdmin));
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.pause(), Ok(()));
+            assert!(contract.is_paused());
+        }
+
+        /// Tests that an unset unlock time is rejected.
+        ///
+        /// This test verifies that:
+        /// 1. A deposit with an unlock time of zero is rejected with `InvalidUnlockTime`.
+        /// 2. The rejection holds even at a zero block timestamp.
+        /// 3. No schedule is created.
+        #[ink::test]
+        fn test_deposit_fund_zero_unlock_time() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+            set_value_transferred::<DefaultEnvironment>(100);
+
+            // Act & Assert
+            assert_eq!(contract.deposit_fund(accounts.bob, 0), Err(Error::InvalidUnlockTime));
+            assert_eq!(contract.deposit_fund_irrevocable(accounts.bob, 0), Err(Error::InvalidUnlockTime));
+            assert_eq!(contract.deposit_fund_linear(accounts.bob, 0, 0), Err(Error::InvalidUnlockTime));
+
+            set_block_timestamp::<DefaultEnvironment>(0);
+            assert_eq!(contract.deposit_fund(accounts.bob, 0), Err(Error::InvalidUnlockTime));
+            assert_eq!(contract.stats().total_schedules, 0);
+            assert_eq!(contract.deposit_fund(accounts.bob, 1), Ok(0));
+        }
+
+        /// Tests withdrawing funds straight into a staking contract.
+        ///
+        /// This test verifies that:
+        /// 1. The withdrawn amount, after the fee, is staked on behalf of the beneficiary.
+        /// 2. Nothing is transferred to the beneficiary.
+        /// 3. A failing staking call makes the withdrawal fail.
+        #[ink::test]
+        fn test_withdraw_and_stake() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let staking = AccountId::from([0x09; 32]);
+            let failing_staking = AccountId::from([0x0A; 32]);
+            mock_stake::fail(failing_staking);
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+            assert_eq!(contract.set_fee(100, accounts.eve), Ok(()));
+
+            set_value_transferred::<DefaultEnvironment>(1000);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(0));
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 2000), Ok(1));
+
+            // Act & Assert
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.withdraw_and_stake(staking), Err(Error::NoFundsAvailable));
+
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 1000);
+            let initial_balance = get_account_balance::<DefaultEnvironment>(accounts.bob).expect(
+                "Failed to get initial balance"
+            );
+            assert_eq!(contract.withdraw_and_stake(staking), Ok(990));
+            assert_eq!(mock_stake::stakes(), vec![(staking, accounts.bob, 990)]);
+            assert_eq!(get_account_balance::<DefaultEnvironment>(accounts.bob), Ok(initial_balance));
+            assert_eq!(contract.total_received_of(accounts.bob), 990);
+
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 2000);
+            assert_eq!(contract.withdraw_and_stake(failing_staking), Err(Error::StakeFailed));
+            assert_eq!(mock_stake::stakes().len(), 1);
+        }
+
+        /// Tests the human-readable error messages.
+        ///
+        /// This test verifies that:
+        /// 1. Every known error code maps to a non-empty message.
+        /// 2. Codes map to the message of the variant with that discriminant.
+        /// 3. Unknown codes map to an empty message.
+        #[ink::test]
+        fn test_error_message() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            let contract = Vesting::new();
+
+            // Act & Assert
+            for code in 0..=Error::RelayNotAllowed as u8 {
+                assert!(!contract.error_message(code).is_empty(), "no message for code {code}");
+            }
+            assert_eq!(contract.error_message(Error::ZeroAmount as u8), b"The amount must not be zero");
+            assert_eq!(contract.error_message(Error::Paused as u8), b"The contract is paused");
+            assert_eq!(contract.error_message(Error::RelayNotAllowed as u8 + 1), Vec::<u8>::new());
+            assert_eq!(contract.error_message(u8::MAX), Vec::<u8>::new());
+        }
+
+        /// Tests locking the beneficiary of a schedule.
+        ///
+        /// This test verifies that:
+        /// 1. Only the beneficiary can lock a schedule.
+        /// 2. A locked schedule cannot be reassigned by its owner.
+        /// 3. Other schedules of the owner can still be reassigned.
+        #[ink::test]
+        fn test_lock_beneficiary() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(0));
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(1));
+
+            // Act & Assert
+            assert_eq!(contract.lock_beneficiary(0), Err(Error::NotBeneficiary));
+            assert_eq!(contract.lock_beneficiary(7), Err(Error::ScheduleNotFound));
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.lock_beneficiary(0), Ok(()));
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.reassign_beneficiary(0, accounts.charlie), Err(Error::BeneficiaryLocked));
+            assert_eq!(contract.get_schedule(0).map(|s| s.beneficiary), Some(accounts.bob));
+            assert_eq!(contract.reassign_beneficiary(1, accounts.charlie), Ok(()));
+            assert_eq!(contract.get_schedule(1).map(|s| s.beneficiary), Some(accounts.charlie));
+        }
+
+        /// Tests the co-signer approval of large revocations.
+        ///
+        /// This test verifies that:
+        /// 1. Only the co-signer can approve a revocation.
+        /// 2. A schedule above the threshold cannot be revoked without approval.
+        /// 3. Schedules at or below the threshold are revoked without approval.
+        /// 4. An approved schedule is revoked and refunded.
+        /// 5. Setting the co-signer emits `ConfigAccountChanged`.
+        /// 6. Approvals are cleared when the schedule changes owner, is renounced or is
+        ///    withdrawn in an emergency.
+        #[ink::test]
+        fn test_approve_revoke() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+            assert_eq!(contract.set_large_amount_threshold(500), Ok(()));
+            assert_eq!(contract.set_co_signer(Some(accounts.charlie)), Ok(()));
+            let events: Vec<_> = recorded_events().collect();
+            let event = <ConfigAccountChanged as scale::Decode>::decode(
+                &mut &events[events.len() - 1].data[..]
+            ).expect("Failed to decode ConfigAccountChanged event");
+            assert_eq!(event.param, ConfigParam::CoSigner as u8);
+            assert_eq!((event.old_account, event.new_account), (None, Some(accounts.charlie)));
+
+            set_value_transferred::<DefaultEnvironment>(1000);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(0));
+            set_value_transferred::<DefaultEnvironment>(500);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(1));
+            set_value_transferred::<DefaultEnvironment>(1000);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(2));
+
+            // Act & Assert
+            assert_eq!(contract.revoke(0), Err(Error::RevokeNotApproved));
+            assert_eq!(contract.revoke(1), Ok(()));
+            assert_eq!(contract.revoke_all_for(accounts.bob), Ok(0));
+
+            assert_eq!(contract.approve_revoke(0), Err(Error::NotCoSigner));
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.approve_revoke(1), Err(Error::ScheduleNotFound));
+            assert_eq!(contract.approve_revoke(0), Ok(()));
+            assert_eq!(contract.approve_revoke(2), Ok(()));
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.revoke(0), Ok(()));
+            assert_eq!(contract.revoke_all_for(accounts.bob), Ok(1000));
+            assert_eq!(contract.locked_of(accounts.bob), 0);
+
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(3));
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(4));
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(5));
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            for id in 3..=5 {
+                assert_eq!(contract.approve_revoke(id), Ok(()));
+            }
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.reassign_owner(3, accounts.django), Ok(()));
+            assert_eq!(contract.revoke_approvals.get(3), None);
+            set_caller::<DefaultEnvironment>(accounts.django);
+            assert_eq!(contract.revoke(3), Err(Error::RevokeNotApproved));
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.renounce(4), Ok(()));
+            assert_eq!(contract.revoke_approvals.get(4), None);
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.emergency_withdraw_request(5), Ok(()));
+            set_block_timestamp::<DefaultEnvironment>(initial_time + EMERGENCY_WITHDRAW_DELAY);
+            assert_eq!(contract.emergency_withdraw_execute(5), Ok(1000));
+            assert_eq!(contract.revoke_approvals.get(5), None);
+        }
+
+        /// Tests withdrawing funds on behalf of a beneficiary through a relayer.
+        ///
+        /// This test verifies that:
+        /// 1. Withdrawals of a beneficiary that did not allow relaying are rejected.
+        /// 2. A tip above the beneficiary's cap, or a cap above the maximum, is rejected.
+        /// 3. Nothing is relayed while the beneficiary has no matured funds.
+        /// 4. The relayer receives the tip and the beneficiary the remainder.
+        #[ink::test]
+        fn test_withdraw_fund_relayed() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(1000);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(0));
+
+            let bob_balance = get_account_balance::<DefaultEnvironment>(accounts.bob).expect(
+                "Failed to get beneficiary balance"
+            );
+            let charlie_balance = get_account_balance::<DefaultEnvironment>(accounts.charlie).expect(
+                "Failed to get relayer balance"
+            );
+
+            // Act & Assert
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.withdraw_fund_relayed(accounts.bob, 0), Err(Error::RelayNotAllowed));
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_relayer_tip_cap(Some(101)), Err(Error::TipTooHigh));
+            assert_eq!(contract.set_relayer_tip_cap(Some(50)), Ok(()));
+            set_caller::<DefaultEnvironment>(accounts.django);
+            assert_eq!(contract.set_relayer_tip_cap(Some(50)), Ok(()));
+
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.withdraw_fund_relayed(accounts.bob, 51), Err(Error::TipTooHigh));
+            assert_eq!(contract.withdraw_fund_relayed(accounts.bob, 50), Err(Error::NoFundsAvailable));
+            assert_eq!(contract.withdraw_fund_relayed(accounts.django, 50), Err(Error::NoSchedules));
+
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 1000);
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_relayer_tip_cap(None), Ok(()));
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.withdraw_fund_relayed(accounts.bob, 50), Err(Error::RelayNotAllowed));
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_relayer_tip_cap(Some(50)), Ok(()));
+
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.withdraw_fund_relayed(accounts.bob, 50), Ok(995));
+            assert_eq!(get_account_balance::<DefaultEnvironment>(accounts.bob), Ok(bob_balance + 995));
+            assert_eq!(get_account_balance::<DefaultEnvironment>(accounts.charlie), Ok(charlie_balance + 5));
+            assert_eq!(contract.get_schedule(0), None);
+        }
+
+        /// Tests the vesting progress of schedules of each kind.
+        ///
+        /// This test verifies that:
+        /// 1. A cliff schedule goes from 0 to 10000 at its unlock time.
+        /// 2. A linear schedule progresses pro rata over its duration.
+        /// 3. A tranche schedule progresses tranche by tranche.
+        /// 4. A missing schedule has no progress.
+        #[ink::test]
+        fn test_vesting_progress() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(300);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(0));
+            assert_eq!(contract.deposit_fund_linear(accounts.bob, initial_time, 3000), Ok(1));
+            let tranches = vec![(initial_time + 1000, 100), (initial_time + 2000, 200)];
+            assert_eq!(contract.deposit_fund_tranches(accounts.bob, tranches), Ok(2));
+
+            // Act & Assert
+            assert_eq!(contract.vesting_progress(0), 0);
+            assert_eq!(contract.vesting_progress(1), 0);
+            assert_eq!(contract.vesting_progress(2), 0);
+            assert_eq!(contract.vesting_progress(3), 0);
+
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 999);
+            assert_eq!(contract.vesting_progress(0), 0);
+            assert_eq!(contract.vesting_progress(1), 3300);
+
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 1000);
+            assert_eq!(contract.vesting_progress(0), 10_000);
+            assert_eq!(contract.vesting_progress(1), 3333);
+            assert_eq!(contract.vesting_progress(2), 3333);
+
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 3000);
+            assert_eq!(contract.vesting_progress(1), 10_000);
+            assert_eq!(contract.vesting_progress(2), 10_000);
+        }
+
+        /// Tests that schedule IDs are never reused.
+        ///
+        /// This test verifies that:
+        /// 1. Removing a schedule does not free its ID.
+        /// 2. A new schedule gets an ID strictly greater than every previous one.
+        /// 3. The removed ID does not resolve to the new schedule.
+        #[ink::test]
+        fn test_schedule_ids_are_monotonic() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            let old_id = contract.deposit_fund(accounts.bob, initial_time + 1000).expect("Deposit failed");
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 1000);
+            assert_eq!(contract.withdraw_fund(), Ok(100));
+            assert_eq!(contract.get_schedule(old_id), None);
+
+            // Act
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_value_transferred::<DefaultEnvironment>(200);
+            let new_id = contract.deposit_fund(accounts.bob, initial_time + 2000).expect("Deposit failed");
+
+            // Assert
+            assert!(new_id > old_id);
+            assert_eq!(contract.get_schedule(old_id), None);
+            assert_eq!(contract.get_schedule(new_id).map(|s| s.amount), Some(200));
+            assert_eq!(contract.claimable_amount(old_id), 0);
+        }
+
+        /// Tests withdrawing only part of the available funds.
+        ///
+        /// This test verifies that:
+        /// 1. Zero and amounts above the available funds are rejected.
+        /// 2. A partial withdrawal is taken from the oldest schedules first.
+        /// 3. The remainder stays claimable and can be withdrawn later.
+        #[ink::test]
+        fn test_withdraw_partial() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(0));
+            set_value_transferred::<DefaultEnvironment>(300);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(1));
+
+            // Act & Assert
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 1000);
+            assert_eq!(contract.withdraw_partial(0), Err(Error::ZeroAmount));
+            assert_eq!(contract.withdraw_partial(401), Err(Error::ExceedsAvailable));
+
+            assert_eq!(contract.withdraw_partial(200), Ok(200));
+            assert_eq!(contract.get_schedule(0), None);
+            assert_eq!(contract.claimable_amount(1), 200);
+            assert_eq!(contract.locked_of(accounts.bob), 200);
+
+            assert_eq!(contract.withdraw_partial(200), Ok(200));
+            assert_eq!(contract.get_schedule(1), None);
+            assert_eq!(contract.beneficiary_to_ids.get(accounts.bob), None);
+            assert_eq!(contract.withdraw_fund(), Err(Error::NoSchedules));
+            contract.assert_tvl_invariant();
+        }
+
+        /// Tests that each contract only accepts the funds of its mode.
+        ///
+        /// This test verifies that:
+        /// 1. A native contract rejects token deposits.
+        /// 2. A token contract rejects native deposits, top-ups and staking.
+        /// 3. Each contract reports its mode.
+        #[ink::test]
+        fn test_vesting_mode() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let token = AccountId::from([0x0B; 32]);
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut native = Vesting::new();
+            let mut psp22 = Vesting::new_psp22(token);
+
+            // Act & Assert
+            assert_eq!(native.mode(), VestingMode::Native);
+            assert_eq!(psp22.mode(), VestingMode::Token(token));
+
+            assert_eq!(
+                native.deposit_fund_token(accounts.bob, initial_time + 1000, 100),
+                Err(Error::WrongMode)
+            );
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(psp22.deposit_fund(accounts.bob, initial_time + 1000), Err(Error::WrongMode));
+            assert_eq!(psp22.deposit_fund_linear(accounts.bob, initial_time, 1000), Err(Error::WrongMode));
+            let entries = vec![(accounts.bob, initial_time + 1000, 100)];
+            assert_eq!(psp22.deposit_fund_batch(entries), Err(Error::WrongMode));
+            assert_eq!(psp22.top_up(0), Err(Error::WrongMode));
+            assert_eq!(psp22.withdraw_and_stake(accounts.charlie), Err(Error::WrongMode));
+        }
+
+        /// Tests the audit trail of configuration changes.
+        ///
+        /// This test verifies that:
+        /// 1. Changing the fee emits `ConfigChanged` with the old and new fee.
+        /// 2. A rejected change emits nothing.
+        /// 3. Flags are reported as 0 or 1.
+        /// 4. Changing the fee recipient emits `ConfigAccountChanged` with both accounts.
+        #[ink::test]
+        fn test_config_changed_event() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            let mut contract = Vesting::new();
+            let last_config_change = || {
+                let events: Vec<_> = recorded_events().collect();
+                let event = <ConfigChanged as scale::Decode>::decode(
+                    &mut &events[events.len() - 1].data[..]
+                ).expect("Failed to decode ConfigChanged event");
+                (events.len(), event)
+            };
+
+            // Act & Assert
+            assert_eq!(contract.set_fee(100, accounts.eve), Ok(()));
+            let (count, event) = last_config_change();
+            assert_eq!(event.param, ConfigParam::FeeBps as u8);
+            assert_eq!((event.old_value, event.new_value), (0, 100));
+
+            assert_eq!(contract.set_fee(250, accounts.eve), Ok(()));
+            let (_, event) = last_config_change();
+            assert_eq!((event.old_value, event.new_value), (100, 250));
+
+            assert_eq!(contract.set_fee(MAX_FEE_BPS + 1, accounts.eve), Err(Error::FeeTooHigh));
+            assert_eq!(last_config_change().0, count + 2);
+
+            assert_eq!(contract.set_fee(250, accounts.bob), Ok(()));
+            let events: Vec<_> = recorded_events().collect();
+            let event = <ConfigAccountChanged as scale::Decode>::decode(
+                &mut &events[events.len() - 2].data[..]
+            ).expect("Failed to decode ConfigAccountChanged event");
+            assert_eq!(event.param, ConfigParam::FeeRecipient as u8);
+            assert_eq!((event.old_account, event.new_account), (Some(accounts.eve), Some(accounts.bob)));
+
+            assert_eq!(contract.pause(), Ok(()));
+            let (_, event) = last_config_change();
+            assert_eq!(event.param, ConfigParam::Paused as u8);
+            assert_eq!((event.old_value, event.new_value), (0, 1));
+        }
+
+        /// Tests the admin distribution of matured funds to several beneficiaries.
+        ///
+        /// This test verifies that:
+        /// 1. Only the admin can distribute.
+        /// 2. Each beneficiary with matured funds is paid its own funds.
+        /// 3. Beneficiaries without schedules or with locked funds are skipped.
+        #[ink::test]
+        fn test_distribute_matured() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(1000);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(0));
+            set_value_transferred::<DefaultEnvironment>(500);
+            assert_eq!(contract.deposit_fund(accounts.charlie, initial_time + 1000), Ok(1));
+            set_value_transferred::<DefaultEnvironment>(200);
+            assert_eq!(contract.deposit_fund(accounts.eve, initial_time + 5000), Ok(2));
+
+            let bob_balance = get_account_balance::<DefaultEnvironment>(accounts.bob).expect(
+                "Failed to get bob balance"
+            );
+            let charlie_balance = get_account_balance::<DefaultEnvironment>(accounts.charlie).expect(
+                "Failed to get charlie balance"
+            );
+            let beneficiaries = vec![accounts.bob, accounts.django, accounts.eve, accounts.charlie];
+
+            // Act & Assert
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 1000);
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.distribute_matured(beneficiaries.clone()), Err(Error::NotAdmin));
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(
+                contract.distribute_matured(beneficiaries.clone()),
+                Ok(vec![(accounts.bob, 1000), (accounts.charlie, 500)])
+            );
+            assert_eq!(get_account_balance::<DefaultEnvironment>(accounts.bob), Ok(bob_balance + 1000));
+            assert_eq!(
+                get_account_balance::<DefaultEnvironment>(accounts.charlie),
+                Ok(charlie_balance + 500)
+            );
+            assert!(contract.get_schedule(2).is_some());
+
+            // Nothing left to distribute
+            assert_eq!(contract.distribute_matured(beneficiaries), Ok(Vec::new()));
+            contract.assert_tvl_invariant();
+        }
+
+        /// Tests the compact storage of schedules vesting to their own owner.
+        ///
+        /// This test verifies that:
+        /// 1. The beneficiary is not stored when it is the owner, saving storage.
+        /// 2. The beneficiary is stored when it differs from the owner.
+        /// 3. Both schedules read back with the right owner and beneficiary.
+        #[ink::test]
+        fn test_schedule_storage_packing() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            // Act
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(0));
+            assert_eq!(contract.deposit_fund(accounts.charlie, initial_time + 1000), Ok(1));
+
+            // Assert
+            let own = contract.schedules.get(0).expect("Schedule 0 not stored");
+            let other = contract.schedules.get(1).expect("Schedule 1 not stored");
+            assert_eq!(own.beneficiary, None);
+            assert_eq!(other.beneficiary, Some(accounts.charlie));
+            assert!(scale::Encode::encoded_size(&own) < scale::Encode::encoded_size(&other));
+
+            let own = contract.get_schedule(0).expect("Schedule 0 not found");
+            assert_eq!((own.owner, own.beneficiary), (accounts.bob, accounts.bob));
+            let other = contract.get_schedule(1).expect("Schedule 1 not found");
+            assert_eq!((other.owner, other.beneficiary), (accounts.bob, accounts.charlie));
+            assert_eq!(contract.schedule_count(accounts.bob), 1);
+        }
+
+        /// Tests querying the time and amount of the next unlock of a beneficiary.
+        ///
+        /// This test verifies that:
+        /// 1. Without schedules there is no next unlock.
+        /// 2. The amount of the schedule with the earliest future unlock time is returned.
+        /// 3. The amounts of schedules unlocking at the same earliest time are added up.
+        /// 4. Once every unlock time has passed there is no next unlock.
+        /// 5. A tranche schedule reports its earliest future tranche, not its last one.
+        #[ink::test]
+        fn test_next_unlock_detail() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            // Act & Assert
+            assert_eq!(contract.next_unlock_detail(accounts.bob), None);
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 3000), Ok(0));
+            set_value_transferred::<DefaultEnvironment>(200);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 2000), Ok(1));
+            assert_eq!(contract.next_unlock_detail(accounts.bob), Some((initial_time + 2000, 200)));
+
+            set_value_transferred::<DefaultEnvironment>(50);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 2000), Ok(2));
+            assert_eq!(contract.next_unlock_detail(accounts.bob), Some((initial_time + 2000, 250)));
+
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 2000);
+            assert_eq!(contract.next_unlock_detail(accounts.bob), Some((initial_time + 3000, 100)));
+
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 3000);
+            assert_eq!(contract.next_unlock_detail(accounts.bob), None);
+
+            let unlocks = vec![
+                (initial_time + 3500, 30),
+                (initial_time + 4000, 20),
+                (initial_time + 5000, 50)
+            ];
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund_tranches(accounts.bob, unlocks), Ok(3));
+            assert_eq!(contract.next_unlock_detail(accounts.bob), Some((initial_time + 3500, 30)));
+
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 3500);
+            assert_eq!(contract.next_unlock_detail(accounts.bob), Some((initial_time + 4000, 20)));
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 4000), Ok(4));
+            assert_eq!(contract.next_unlock_detail(accounts.bob), Some((initial_time + 4000, 120)));
+        }
+
+        /// Tests that withdrawals follow checks-effects-interactions.
+        ///
+        /// This test verifies that:
+        /// 1. Settling a withdrawal updates the schedules, indexes, totals and claim history
+        ///    without moving any tokens.
+        /// 2. A call re-entering at that point finds nothing left to withdraw.
+        /// 3. Only the interaction step moves the tokens to the recipient.
+        #[ink::test]
+        fn test_withdraw_updates_state_before_transfer() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let token = accounts.frank;
+            let contract_id = accounts.django;
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_callee::<DefaultEnvironment>(contract_id);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new_psp22(token);
+            mock_token::mint(accounts.alice, 100);
+            assert_eq!(contract.deposit_fund_token(accounts.bob, initial_time + 1000, 100), Ok(0));
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 1000);
+            set_caller::<DefaultEnvironment>(accounts.bob);
+
+            // Act
+            let payout = Payout::Transfer(accounts.bob);
+            let settled = contract.settle_matured(accounts.bob, payout, usize::MAX, Balance::MAX);
+
+            // Assert
+            assert_eq!(settled, Ok((100, 100, vec![0])));
+            assert_eq!(contract.get_schedule(0), None);
+            assert_eq!(contract.schedule_count(accounts.bob), 0);
+            assert_eq!(contract.locked_of(accounts.bob), 0);
+            assert_eq!(contract.total_locked(), 0);
+            assert_eq!(contract.total_received_of(accounts.bob), 100);
+            assert_eq!(mock_token::balance_of(accounts.bob), 0);
+            assert_eq!(mock_token::balance_of(contract_id), 100);
+
+            // A re-entrant withdrawal before the transfer finds nothing to take
+            assert_eq!(contract.withdraw_fund(), Err(Error::NoSchedules));
+
+            assert_eq!(contract.send_payout(accounts.bob, payout, 100, &[0]), Ok(()));
+            assert_eq!(mock_token::balance_of(accounts.bob), 100);
+            assert_eq!(mock_token::balance_of(contract_id), 0);
+            contract.assert_tvl_invariant();
+        }
+
+        /// Tests querying the schedules unlocking in a time range.
+        ///
+        /// This test verifies that:
+        /// 1. Only schedules whose unlock time falls in the range are returned.
+        /// 2. Both ends of the range are included.
+        /// 3. A page bounds the scan to part of the active schedules.
+        #[ink::test]
+        fn test_schedules_unlocking_between() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let unlocking_ids = |contract: &Vesting, start: Timestamp, end: Timestamp, page| -> Vec<u64> {
+                let mut ids: Vec<u64> = contract
+                    .schedules_unlocking_between(start, end, page)
+                    .into_iter()
+                    .map(|(id, _, _)| id)
+                    .collect();
+                ids.sort();
+                ids
+            };
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(0));
+            set_value_transferred::<DefaultEnvironment>(200);
+            assert_eq!(contract.deposit_fund(accounts.charlie, initial_time + 2000), Ok(1));
+            set_value_transferred::<DefaultEnvironment>(300);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 3000), Ok(2));
+            assert_eq!(contract.deposit_fund(accounts.eve, initial_time + 5000), Ok(3));
+
+            // Act & Assert
+            assert_eq!(
+                contract.schedules_unlocking_between(initial_time + 1500, initial_time + 2500, None),
+                vec![(1, accounts.charlie, 200)]
+            );
+            assert_eq!(
+                unlocking_ids(&contract, initial_time + 1000, initial_time + 3000, None),
+                vec![0, 1, 2]
+            );
+            assert!(unlocking_ids(&contract, initial_time + 3001, initial_time + 4999, None).is_empty());
+            assert_eq!(unlocking_ids(&contract, initial_time, initial_time + 5000, Some((0, 2))).len(), 2);
+            assert_eq!(unlocking_ids(&contract, initial_time, initial_time + 5000, Some((3, 10))).len(), 1);
+            assert!(unlocking_ids(&contract, initial_time, initial_time + 5000, Some((4, 10))).is_empty());
+        }
+
+        /// Tests paying out through the `on_vest_received` callback of a receiving contract.
+        ///
+        /// This test verifies that:
+        /// 1. A receiver that accepts the callback gets the funds with the released schedule IDs.
+        /// 2. A receiver that rejects the callback is credited the funds instead.
+        /// 3. The callback cannot be enabled when vesting a PSP22 token.
+        #[ink::test]
+        fn test_vest_callback() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(0));
+            assert_eq!(contract.deposit_fund(accounts.charlie, initial_time + 1000), Ok(1));
+            set_value_transferred::<DefaultEnvironment>(200);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(2));
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_vest_callback(true), Ok(()));
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.set_vest_callback(true), Ok(()));
+            mock_receiver::fail(accounts.charlie);
+            let bob_balance = get_account_balance::<DefaultEnvironment>(accounts.bob).expect(
+                "Failed to get bob balance"
+            );
+
+            // Act
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 1000);
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.withdraw_fund(), Ok(300));
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.withdraw_fund(), Ok(100));
+
+            // Assert
+            assert_eq!(mock_receiver::receipts(), vec![(accounts.bob, vec![0, 2], 300)]);
+            assert_eq!(get_account_balance::<DefaultEnvironment>(accounts.bob), Ok(bob_balance));
+            assert_eq!(contract.credited_balances.get(accounts.bob), None);
+            assert_eq!(contract.credited_balances.get(accounts.charlie), Some(100));
+            contract.assert_tvl_invariant();
+
+            let mut psp22 = Vesting::new_psp22(accounts.frank);
+            assert_eq!(psp22.set_vest_callback(true), Err(Error::WrongMode));
+        }
+
+        /// Tests feature detection.
         ///
         /// This test verifies that:
-        /// 1. When the `id` counter reaches its maximum value (u64::MAX).
-        /// 2. Attempting to create a new vesting schedule results in an `Error::IdOverflow`.
+        /// 1. Core features are reported as supported.
+        /// 2. Unknown feature IDs are reported as unsupported.
         #[ink::test]
-        fn test_id_overflow() {
+        fn test_supports() {
             // Arrange
             let accounts = default_accounts::<DefaultEnvironment>();
-            let unlocktime = 242208000;
-            let mut vesting = Vesting::new();
-            ink::env::debug_println!("---- initial id: {}", vesting.id);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            let contract = Vesting::new();
 
-            vesting.id = u64::MAX; // Set id to the maximum value
-            ink::env::debug_println!("---- maximum id: {}", vesting.id);
+            // Act & Assert
+            assert!(contract.supports(Feature::CliffVesting as u32));
+            assert!(contract.supports(Feature::LinearVesting as u32));
+            assert!(contract.supports(Feature::Psp22Mode as u32));
+            assert!(contract.supports(Feature::Revocation as u32));
+            for feature in SUPPORTED_FEATURES {
+                assert!(contract.supports(feature as u32));
+            }
+            assert!(!contract.supports(SUPPORTED_FEATURES.len() as u32));
+            assert!(!contract.supports(u32::MAX));
+        }
+
+        /// Tests the cap on the funds withdrawn per window.
+        ///
+        /// This test verifies that:
+        /// 1. Only the admin can set the cap.
+        /// 2. Withdrawals are accepted until the cap of the window is reached.
+        /// 3. A withdrawal that would exceed the cap fails without releasing anything.
+        /// 4. Withdrawals resume once a new window starts.
+        #[ink::test]
+        fn test_outflow_cap() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let window: Timestamp = 1000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(300);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 10), Ok(0));
+            set_value_transferred::<DefaultEnvironment>(200);
+            assert_eq!(contract.deposit_fund(accounts.charlie, initial_time + 10), Ok(1));
+            assert_eq!(contract.deposit_fund(accounts.eve, initial_time + 10), Ok(2));
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_outflow_cap(500, window), Err(Error::NotAdmin));
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.set_outflow_cap(500, window), Ok(()));
+
+            // Act & Assert
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 100);
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.withdraw_fund(), Ok(300));
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.withdraw_fund(), Ok(200));
+
+            // The window is exhausted
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 1099);
+            set_caller::<DefaultEnvironment>(accounts.eve);
+            assert_eq!(contract.withdraw_fund(), Err(Error::OutflowCapReached));
+            assert_eq!(contract.withdraw_schedule(2), Err(Error::OutflowCapReached));
+            assert_eq!(contract.get_schedule(2).map(|schedule| schedule.claimable), Some(200));
+
+            // A new window starts once the previous one is over
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 1100);
+            assert_eq!(contract.withdraw_fund(), Ok(200));
+            contract.assert_tvl_invariant();
+        }
+
+        /// Tests splitting part of a schedule off into a new one.
+        ///
+        /// This test verifies that:
+        /// 1. Only the owner can split a schedule.
+        /// 2. Splitting off nothing, the whole amount or more is rejected.
+        /// 3. The new schedule gets the amount with the same beneficiary and unlock time.
+        /// 4. The total amount is conserved and both schedules can be withdrawn.
+        /// 5. An unlocked schedule can no longer be split.
+        /// 6. A schedule above the large amount threshold cannot be split into pieces revocable
+        ///    without the co-signer's approval.
+        #[ink::test]
+        fn test_split_schedule() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let unlock_time = initial_time + 1000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(1000);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(0));
+
+            // Act & Assert
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.split_schedule(0, 400), Err(Error::NotOwner));
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.split_schedule(0, 0), Err(Error::ZeroAmount));
+            assert_eq!(contract.split_schedule(0, 1000), Err(Error::InvalidSplit));
+            assert_eq!(contract.split_schedule(0, 1001), Err(Error::InvalidSplit));
+            assert_eq!(contract.split_schedule(0, 400), Ok(1));
+
+            let original = contract.get_schedule(0).expect("Schedule 0 not found");
+            let split = contract.get_schedule(1).expect("Schedule 1 not found");
+            assert_eq!(original.amount, 600);
+            assert_eq!(split.amount, 400);
+            assert_eq!((split.beneficiary, split.unlock_time), (accounts.bob, unlock_time));
+            assert_eq!(original.amount + split.amount, 1000);
+            assert_eq!(contract.locked_of(accounts.bob), 1000);
+            assert_eq!(contract.total_locked(), 1000);
+            assert_eq!(contract.get_owned_schedule_ids(accounts.alice), vec![0, 1]);
+            contract.assert_tvl_invariant();
+
+            set_block_timestamp::<DefaultEnvironment>(unlock_time);
+            assert_eq!(contract.split_schedule(0, 100), Err(Error::AlreadyVested));
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.withdraw_fund(), Ok(1000));
+            contract.assert_tvl_invariant();
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.set_large_amount_threshold(500), Ok(()));
+            assert_eq!(contract.set_co_signer(Some(accounts.charlie)), Ok(()));
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time + 1000), Ok(2));
+            assert_eq!(contract.split_schedule(2, 500), Err(Error::RevokeNotApproved));
+            assert_eq!(contract.revoke(2), Err(Error::RevokeNotApproved));
+            assert_eq!(contract.get_schedule(2).map(|s| s.amount), Some(1000));
 
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.approve_revoke(2), Ok(()));
             set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.split_schedule(2, 500), Ok(3));
+            assert_eq!(contract.revoke(3), Ok(()));
+            contract.assert_tvl_invariant();
+        }
+
+        /// Tests merging schedules with identical terms.
+        ///
+        /// This test verifies that:
+        /// 1. Schedules with a different beneficiary or unlock time cannot be merged.
+        /// 2. Fewer than two schedules, or a repeated ID, cannot be merged.
+        /// 3. Only the owner can merge schedules.
+        /// 4. A combined amount above the maximum schedule amount is rejected.
+        /// 5. Merged schedules are replaced by one holding their combined amount.
+        #[ink::test]
+        fn test_merge_schedules() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let unlock_time = initial_time + 1000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(0));
+            set_value_transferred::<DefaultEnvironment>(200);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(1));
+            set_value_transferred::<DefaultEnvironment>(300);
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time), Ok(2));
+            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time + 1), Ok(3));
+            assert_eq!(contract.deposit_fund(accounts.charlie, unlock_time), Ok(4));
+
+            // Act & Assert
+            assert_eq!(contract.merge_schedules(vec![0, 3]), Err(Error::CannotMerge));
+            assert_eq!(contract.merge_schedules(vec![0, 4]), Err(Error::CannotMerge));
+            assert_eq!(contract.merge_schedules(vec![0]), Err(Error::CannotMerge));
+            assert_eq!(contract.merge_schedules(vec![0, 0]), Err(Error::CannotMerge));
+            assert_eq!(contract.merge_schedules(vec![0, 9]), Err(Error::ScheduleNotFound));
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.merge_schedules(vec![0, 1]), Err(Error::NotOwner));
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.set_max_schedule_amount(500), Ok(()));
+            assert_eq!(contract.merge_schedules(vec![0, 1, 2]), Err(Error::AmountTooLarge));
+            assert_eq!(contract.set_max_schedule_amount(600), Ok(()));
+            assert_eq!(contract.merge_schedules(vec![0, 1, 2]), Ok(5));
+            assert_eq!(contract.get_schedule(0), None);
+            assert_eq!(contract.get_schedule(1), None);
+            assert_eq!(contract.get_schedule(2), None);
+            let merged = contract.get_schedule(5).expect("Merged schedule not found");
+            assert_eq!(merged.amount, 600);
+            assert_eq!((merged.beneficiary, merged.unlock_time), (accounts.bob, unlock_time));
+            assert_eq!(contract.schedule_count(accounts.bob), 2);
+            assert_eq!(contract.locked_of(accounts.bob), 900);
+            contract.assert_tvl_invariant();
+
+            set_block_timestamp::<DefaultEnvironment>(unlock_time);
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.withdraw_fund(), Ok(600));
+            contract.assert_tvl_invariant();
+        }
+
+        /// Tests a schedule unlocking at a block number.
+        ///
+        /// This test verifies that:
+        /// 1. A zero or past unlock block is rejected.
+        /// 2. Time passing alone does not release the funds.
+        /// 3. The funds are released once the unlock block is reached.
+        #[ink::test]
+        fn test_deposit_fund_by_block() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+            advance_block::<DefaultEnvironment>();
+            let current_block = ink::env::block_number::<DefaultEnvironment>();
+            let unlock_block = current_block + 3;
+
+            // Act & Assert
             set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund_by_block(accounts.bob, 0), Err(Error::InvalidUnlockTime));
+            assert_eq!(
+                contract.deposit_fund_by_block(accounts.bob, current_block),
+                Err(Error::UnlockInPast)
+            );
+            assert_eq!(contract.deposit_fund_by_block(accounts.bob, unlock_block), Ok(0));
+
+            let info = contract.get_schedule(0).expect("Schedule not found");
+            assert_eq!((info.unlock_time, info.unlock_block), (0, Some(unlock_block)));
+            assert_eq!(contract.next_unlock(accounts.bob), None);
+
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 1_000_000);
+            assert_eq!(contract.withdraw_fund(), Err(Error::NoFundsAvailable));
+
+            advance_block::<DefaultEnvironment>();
+            advance_block::<DefaultEnvironment>();
+            assert!(!contract.is_unlocked(0));
+            assert_eq!(contract.withdraw_fund(), Err(Error::NoFundsAvailable));
+
+            advance_block::<DefaultEnvironment>();
+            assert!(contract.is_unlocked(0));
+            assert_eq!(contract.withdraw_fund(), Ok(100));
+            contract.assert_tvl_invariant();
+        }
+
+        /// Tests the report of the actual and tracked balance.
+        ///
+        /// This test verifies that:
+        /// 1. The report matches right after a deposit.
+        /// 2. Raw value sent to the contract shows up as a discrepancy.
+        /// 3. In token mode the token balance is reported, not the native one.
+        #[ink::test]
+        fn test_balance_report() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let contract_account = accounts.django;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_callee::<DefaultEnvironment>(contract_account);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(1_000_000);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(0));
+            set_account_balance::<DefaultEnvironment>(contract_account, 1_000_000);
+            assert_eq!(contract.balance_report(), (1_000_000, 1_000_000));
 
             // Act
-            let result = vesting.deposit_fund(accounts.bob, unlocktime);
+            set_account_balance::<DefaultEnvironment>(contract_account, 1_500_000);
 
             // Assert
-            assert_eq!(result, Err(Error::IdOverflow));
+            assert_eq!(contract.balance_report(), (1_500_000, 1_000_000));
+
+            let token = accounts.frank;
+            let mut psp22 = Vesting::new_psp22(token);
+            mock_token::mint(accounts.alice, 300);
+            assert_eq!(psp22.deposit_fund_token(accounts.bob, initial_time + 1000, 300), Ok(0));
+            assert_eq!(psp22.balance_report(), (300, 300));
+            mock_token::mint(contract_account, 50);
+            assert_eq!(psp22.balance_report(), (350, 300));
         }
 
-        /// Tests the scenario where a beneficiary attempts to withdraw funds before the unlock time.
+        /// Tests the combined dashboard query of a beneficiary.
         ///
         /// This test verifies that:
-        /// 1. Funds are deposited into a vesting schedule with a future unlock time.
-        /// 2. When the beneficiary attempts to withdraw funds before the unlock time.
-        /// 3. The `withdraw_fund` function returns `Err(Error::NoFundsAvailable)`.
+        /// 1. An account without schedules gets an empty dashboard.
+        /// 2. Each field matches the state after deposits and a withdrawal.
         #[ink::test]
-        fn test_withdraw_before_unlock() {
+        fn test_dashboard() {
             // Arrange
             let accounts = default_accounts::<DefaultEnvironment>();
             let initial_time: Timestamp = 242208000;
-            let unlock_time_attempt: Timestamp = initial_time + 1000; // Unlock in the future
-            let amount: Balance = 100;
 
-            // Set the initial caller to Alice (the owner)
             set_caller::<DefaultEnvironment>(accounts.alice);
-            set_block_timestamp::<ink::env::DefaultEnvironment>(initial_time);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
             let mut contract = Vesting::new();
+            assert_eq!(contract.dashboard(accounts.bob), BeneficiaryDashboard {
+                claimable: 0,
+                locked: 0,
+                next_unlock: None,
+                schedule_count: 0,
+                total_received: 0,
+            });
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 1000), Ok(0));
+            assert_eq!(contract.deposit_fund(accounts.bob, initial_time + 2000), Ok(1));
+            set_value_transferred::<DefaultEnvironment>(200);
+            assert_eq!(contract.deposit_fund_linear(accounts.bob, initial_time + 1000, 2000), Ok(2));
 
-            // Act
-            // Simulate a deposit of `amount` tokens from Alice to Bob, with a future unlock time
-            set_value_transferred::<ink::env::DefaultEnvironment>(amount);
-            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time_attempt), Ok(()));
-            
-            // Set Bob as the caller (the beneficiary)
             set_caller::<DefaultEnvironment>(accounts.bob);
-            // Attempt to withdraw before unlock
-            let result = contract.withdraw_fund();
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 1000);
+            assert_eq!(contract.withdraw_schedule(0), Ok(100));
+
+            // Act
+            set_block_timestamp::<DefaultEnvironment>(initial_time + 2000);
+            let dashboard = contract.dashboard(accounts.bob);
 
             // Assert
-            // Verify that the withdrawal fails with the expected error
-            assert_eq!(result, Err(Error::NoFundsAvailable));
+            assert_eq!(dashboard, BeneficiaryDashboard {
+                claimable: 200,
+                locked: 100,
+                next_unlock: Some(initial_time + 3000),
+                schedule_count: 2,
+                total_received: 100,
+            });
         }
 
-        /// Tests the successful withdrawal of funds after the unlock period has passed with multiple vestings.
+        /// Tests that a deposit overflowing the locked totals is rejected by default.
         ///
         /// This test verifies that:
-        /// 1. Multiple funds can be deposited into different vesting schedules for the same beneficiary.
-        /// 2. Each vesting can have different deposited amount and unlock time
-        /// 3. Funds cannot be withdrawn before the unlock time.
-        /// 4. All funds can be successfully withdrawn after the unlock time.
-        /// 5. The withdrawn amount is the sum of all deposited amounts.
+        /// 1. A deposit overflowing `total_locked` fails with `Error::AmountOverflow`.
+        /// 2. Nothing is stored for the rejected deposit.
+        #[cfg(not(feature = "saturating-counters"))]
         #[ink::test]
-        fn tesst_successful_withdrawal_after_unlock() {
+        fn test_locked_counters_checked() {
             // Arrange
-            // Get test accounts
             let accounts = default_accounts::<DefaultEnvironment>();
-            // Define initial timestamp
             let initial_time: Timestamp = 242208000;
-            // Define unlock timestamps for each vesting
-            let unlock_time_1: Timestamp = 1820044800; //50 years later
-            let unlock_time_2: Timestamp = 1851580800; //51 years later
-            let unlock_time_3: Timestamp = 1883116800; //52 years later
-            // Define the amount of tokens to transfer for each vesting
-            let amount_1: Balance = 100;
-            let amount_2: Balance = 200;
-            let amount_3: Balance = 300;
-            // Calculate the total amount
-            let total_amount: Balance = amount_1 + amount_2 + amount_3;
 
-            // Set the initial caller to Alice (the owner)
             set_caller::<DefaultEnvironment>(accounts.alice);
-            // Set the initial block timestamp
-            set_block_timestamp::<ink::env::DefaultEnvironment>(initial_time);
-            // Instantiate the vesting contract
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
             let mut contract = Vesting::new();
+            contract.total_locked = u128::MAX - 50;
 
             // Act
-            // Simulate multiple deposits from Alice to Bob, with different unlock times
-            set_value_transferred::<ink::env::DefaultEnvironment>(amount_1);
-            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time_1), Ok(()));
+            set_value_transferred::<DefaultEnvironment>(100);
+            let result = contract.deposit_fund(accounts.bob, initial_time + 1000);
 
-            set_value_transferred::<ink::env::DefaultEnvironment>(amount_2);
-            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time_2), Ok(()));
+            // Assert
+            assert_eq!(result, Err(Error::AmountOverflow));
+            assert_eq!(contract.total_locked(), u128::MAX - 50);
+            assert_eq!(contract.locked_of(accounts.bob), 0);
+            assert_eq!(contract.stats().next_id, 0);
+        }
 
-            set_value_transferred::<ink::env::DefaultEnvironment>(amount_3);
-            assert_eq!(contract.deposit_fund(accounts.bob, unlock_time_3), Ok(()));
+        /// Tests that the locked totals saturate with the `saturating-counters` feature.
+        ///
+        /// This test verifies that:
+        /// 1. A deposit overflowing the totals is accepted.
+        /// 2. The totals are capped at `u128::MAX`.
+        #[cfg(feature = "saturating-counters")]
+        #[ink::test]
+        fn test_locked_counters_saturating() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
 
-            // Advance the block timestamp to a time after all unlocks
-            set_block_timestamp::<ink::env::DefaultEnvironment>(unlock_time_3 + 1);
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+            contract.total_locked = u128::MAX - 50;
+            contract.locked_by_beneficiary.insert(accounts.bob, &(u128::MAX - 50));
 
-            // Set the caller to Bob (the beneficiary)
+            // Act
+            set_value_transferred::<DefaultEnvironment>(100);
+            let result = contract.deposit_fund(accounts.bob, initial_time + 1000);
+
+            // Assert
+            assert_eq!(result, Ok(0));
+            assert_eq!(contract.total_locked(), u128::MAX);
+            assert_eq!(contract.locked_of(accounts.bob), u128::MAX);
+        }
+
+        /// Tests a schedule the beneficiary has to accept before withdrawing.
+        ///
+        /// This test verifies that:
+        /// 1. A pending schedule has nothing claimable, even once unlocked.
+        /// 2. Withdrawing a pending schedule fails with `Error::NotAccepted`.
+        /// 3. Only the beneficiary can accept the schedule.
+        /// 4. The funds can be withdrawn once accepted.
+        /// 5. The owner can revoke a vested schedule while it is pending.
+        /// 6. A partial revocation of a pending schedule refunds it in full and removes it.
+        #[ink::test]
+        fn test_accept_schedule() {
+            // Arrange
+            let accounts = default_accounts::<DefaultEnvironment>();
+            let initial_time: Timestamp = 242208000;
+            let unlock_time: Timestamp = initial_time + 1000;
+
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            set_block_timestamp::<DefaultEnvironment>(initial_time);
+            let mut contract = Vesting::new();
+
+            set_value_transferred::<DefaultEnvironment>(100);
+            assert_eq!(contract.deposit_fund_with_acceptance(accounts.bob, unlock_time), Ok(0));
+            assert_eq!(contract.deposit_fund_with_acceptance(accounts.bob, unlock_time), Ok(1));
+            assert_eq!(contract.deposit_fund_with_acceptance(accounts.bob, unlock_time), Ok(2));
+            assert_eq!(contract.get_schedule(0).map(|s| s.pending_acceptance), Some(true));
+
+            // Act & Assert
             set_caller::<DefaultEnvironment>(accounts.bob);
+            set_block_timestamp::<DefaultEnvironment>(unlock_time);
+            assert_eq!(contract.get_schedule(0).map(|s| s.claimable), Some(0));
+            assert_eq!(contract.withdraw_schedule(0), Err(Error::NotAccepted));
+            assert_eq!(contract.withdraw_fund(), Err(Error::NoFundsAvailable));
 
-            // Get Bob's initial balance
-            let initial_balance = get_account_balance::<DefaultEnvironment>(accounts.bob).expect(
-                "Failed to get initial balance"
-            );
+            set_caller::<DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.accept_schedule(0), Err(Error::NotBeneficiary));
+            set_caller::<DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.accept_schedule(7), Err(Error::ScheduleNotFound));
+            assert_eq!(contract.accept_schedule(0), Ok(()));
+            assert_eq!(contract.accept_schedule(0), Ok(()));
+            assert_eq!(contract.get_schedule(0).map(|s| s.pending_acceptance), Some(false));
+            assert_eq!(contract.withdraw_schedule(0), Ok(100));
 
-            // Attempt to withdraw the funds, which should now be unlocked
-            assert_eq!(contract.withdraw_fund(), Ok(()));
+            // The other schedule vested but was never accepted
+            set_caller::<DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.revoke(1), Ok(()));
+            assert_eq!(contract.get_schedule(1), None);
+            assert_eq!(contract.revoke_partial(2), Ok(100));
+            assert_eq!(contract.get_schedule(2), None);
+            contract.assert_tvl_invariant();
+        }
 
-            // Get Bob's final balance
-            let final_balance = get_account_balance::<DefaultEnvironment>(accounts.bob).expect(
-                "Failed to get final balance"
-            );
+        /// In-memory stand-in for a PSP22 token contract.
+        ///
+        /// Allowances are not modelled: `transfer_from` only checks the balance of `from`.
+        pub(super) mod mock_token {
+            use super::super::{ AccountId, Balance, PSP22Error, TokenCall };
+            use std::{ cell::RefCell, collections::BTreeMap };
 
-            // Assert
-            // Check if the difference between the final and initial balance is equal to the total amount
-            assert_eq!(final_balance - initial_balance, total_amount);
+            thread_local! {
+                static BALANCES: RefCell<BTreeMap<AccountId, Balance>> = const {
+                    RefCell::new(BTreeMap::new())
+                };
+            }
+
+            /// Credits `amount` tokens to `account`.
+            pub fn mint(account: AccountId, amount: Balance) {
+                BALANCES.with(|balances| {
+                    *balances.borrow_mut().entry(account).or_default() += amount;
+                });
+            }
+
+            /// Returns the token balance of `account`.
+            pub fn balance_of(account: AccountId) -> Balance {
+                BALANCES.with(|balances| balances.borrow().get(&account).copied().unwrap_or_default())
+            }
+
+            /// Executes `call` as if made by `contract` on the token.
+            pub fn call(
+                _token: AccountId,
+                contract: AccountId,
+                call: TokenCall
+            ) -> Result<(), PSP22Error> {
+                let (from, to, value) = match call {
+                    TokenCall::Transfer { to, value } => (contract, to, value),
+                    TokenCall::TransferFrom { from, to, value } => (from, to, value),
+                };
+                BALANCES.with(|balances| {
+                    let mut balances = balances.borrow_mut();
+                    let from_balance = balances.get(&from).copied().unwrap_or_default();
+                    if from_balance < value {
+                        return Err(PSP22Error::InsufficientBalance);
+                    }
+                    balances.insert(from, from_balance - value);
+                    *balances.entry(to).or_default() += value;
+                    Ok(())
+                })
+            }
+        }
+
+        /// In-memory stand-in for a contract notified of withdrawals.
+        pub(super) mod mock_notify {
+            use super::super::{ AccountId, Balance };
+            use std::{ cell::RefCell, collections::BTreeSet };
+
+            thread_local! {
+                static CALLS: RefCell<Vec<(AccountId, AccountId, Balance)>> = const {
+                    RefCell::new(Vec::new())
+                };
+                static FAILING: RefCell<BTreeSet<AccountId>> = const {
+                    RefCell::new(BTreeSet::new())
+                };
+            }
+
+            /// Makes every notification sent to `notify_contract` fail.
+            pub fn fail(notify_contract: AccountId) {
+                FAILING.with(|failing| {
+                    failing.borrow_mut().insert(notify_contract);
+                });
+            }
+
+            /// Returns the successful notifications as `(notify_contract, beneficiary, amount)`.
+            pub fn calls() -> Vec<(AccountId, AccountId, Balance)> {
+                CALLS.with(|calls| calls.borrow().clone())
+            }
+
+            /// Records a notification, returning whether it succeeded.
+            pub fn call(notify_contract: AccountId, beneficiary: AccountId, amount: Balance) -> bool {
+                if FAILING.with(|failing| failing.borrow().contains(&notify_contract)) {
+                    return false;
+                }
+                CALLS.with(|calls| calls.borrow_mut().push((notify_contract, beneficiary, amount)));
+                true
+            }
+        }
+
+        /// In-memory stand-in for a staking contract.
+        pub(super) mod mock_stake {
+            use super::super::{ AccountId, Balance };
+            use std::{ cell::RefCell, collections::BTreeSet };
+
+            thread_local! {
+                static STAKES: RefCell<Vec<(AccountId, AccountId, Balance)>> = const {
+                    RefCell::new(Vec::new())
+                };
+                static FAILING: RefCell<BTreeSet<AccountId>> = const {
+                    RefCell::new(BTreeSet::new())
+                };
+            }
+
+            /// Makes every stake sent to `staking_contract` fail.
+            pub fn fail(staking_contract: AccountId) {
+                FAILING.with(|failing| {
+                    failing.borrow_mut().insert(staking_contract);
+                });
+            }
+
+            /// Returns the successful stakes as `(staking_contract, on_behalf_of, amount)`.
+            pub fn stakes() -> Vec<(AccountId, AccountId, Balance)> {
+                STAKES.with(|stakes| stakes.borrow().clone())
+            }
+
+            /// Records a stake, returning whether it succeeded.
+            pub fn call(staking_contract: AccountId, on_behalf_of: AccountId, amount: Balance) -> bool {
+                if FAILING.with(|failing| failing.borrow().contains(&staking_contract)) {
+                    return false;
+                }
+                STAKES.with(|stakes| stakes.borrow_mut().push((staking_contract, on_behalf_of, amount)));
+                true
+            }
+        }
+
+        /// In-memory stand-in for contracts receiving their payouts through `on_vest_received`.
+        pub(super) mod mock_receiver {
+            use super::super::{ AccountId, Balance };
+            use std::{ cell::RefCell, collections::BTreeSet };
+
+            /// A payout received as `(receiver, schedule_ids, amount)`
+            pub type Receipt = (AccountId, Vec<u64>, Balance);
+
+            thread_local! {
+                static RECEIPTS: RefCell<Vec<Receipt>> = const {
+                    RefCell::new(Vec::new())
+                };
+                static FAILING: RefCell<BTreeSet<AccountId>> = const {
+                    RefCell::new(BTreeSet::new())
+                };
+            }
+
+            /// Makes every callback sent to `receiver` fail.
+            pub fn fail(receiver: AccountId) {
+                FAILING.with(|failing| {
+                    failing.borrow_mut().insert(receiver);
+                });
+            }
+
+            /// Returns the payouts accepted so far.
+            pub fn receipts() -> Vec<Receipt> {
+                RECEIPTS.with(|receipts| receipts.borrow().clone())
+            }
+
+            /// Records a payout, returning whether the receiver accepted it.
+            pub fn call(receiver: AccountId, ids: &[u64], amount: Balance) -> bool {
+                if FAILING.with(|failing| failing.borrow().contains(&receiver)) {
+                    return false;
+                }
+                RECEIPTS.with(|receipts| receipts.borrow_mut().push((receiver, ids.to_vec(), amount)));
+                true
+            }
         }
     }
 }